@@ -0,0 +1,104 @@
+use super::lowlevel::*;
+
+/// Box of an ISO base media file (ISO/IEC 14496-12), the container of HEIF, AVIF
+/// and JPEG XL
+pub(crate) struct BmffBox<'a> {
+    /// Box type, e.g. b"meta"
+    pub kind: &'a [u8],
+    /// Contents of the box, excluding size and type
+    pub data: &'a [u8],
+    /// Offset of the contents within the buffer that was walked
+    pub offset: usize,
+}
+
+/// Iterator over a sequence of boxes, either the top-level boxes of a file
+/// or the boxes contained in another box
+pub(crate) struct BmffBoxes<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+pub(crate) fn bmff_boxes(contents: &[u8]) -> BmffBoxes<'_> {
+    BmffBoxes {
+        contents,
+        offset: 0,
+        done: false,
+    }
+}
+
+impl<'a> BmffBoxes<'a> {
+    fn read_box(&self) -> Result<BmffBox<'a>, &'static str> {
+        let contents = self.contents;
+        let offset = self.offset;
+
+        if contents.len() < (offset + 8) {
+            return Err("Truncated in box header");
+        }
+
+        let mut start = offset + 8;
+        let size = match read_u32(false, &contents[offset..offset + 4]) {
+            // box extends to the end of the file
+            0 => contents.len() - offset,
+            // 64-bit size follows the type
+            1 => {
+                let large = contents
+                    .get(offset + 8..offset + 16)
+                    .ok_or("Truncated in box header")?;
+                start += 8;
+                let high = read_u32(false, &large[0..4]) as u64;
+                let low = read_u32(false, &large[4..8]) as u64;
+                ((high << 32) + low) as usize
+            }
+            size => size as usize,
+        };
+
+        if size < start - offset || contents.len() - offset < size {
+            return Err("Truncated in box body");
+        }
+
+        Ok(BmffBox {
+            kind: &contents[offset + 4..offset + 8],
+            data: &contents[start..offset + size],
+            offset: start,
+        })
+    }
+}
+
+impl<'a> Iterator for BmffBoxes<'a> {
+    type Item = Result<BmffBox<'a>, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.contents.len() {
+            return None;
+        }
+
+        let item = self.read_box();
+        match item {
+            Ok(ref item) => self.offset = item.offset + item.data.len(),
+            Err(_) => self.done = true,
+        }
+        Some(item)
+    }
+}
+
+/// Finds the first box of a given type in a sequence of boxes
+pub(crate) fn find_box<'a>(
+    contents: &'a [u8],
+    kind: &[u8],
+) -> Result<Option<BmffBox<'a>>, &'static str> {
+    for item in bmff_boxes(contents) {
+        let item = item?;
+        if item.kind == kind {
+            return Ok(Some(item));
+        }
+    }
+    Ok(None)
+}
+
+/// EXIF data in HEIF items and JPEG XL boxes starts with the offset of the TIFF
+/// header, counted after the offset itself. Returns the TIFF block that follows.
+pub(crate) fn skip_tiff_header_offset(data: &[u8]) -> Option<&[u8]> {
+    let tiff_offset = read_u32(false, data.get(0..4)?) as usize;
+    data.get(4usize.checked_add(tiff_offset)?..)
+}
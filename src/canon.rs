@@ -0,0 +1,62 @@
+use super::lowlevel::*;
+use super::tiff::*;
+use super::types::*;
+
+/// Returns true if the Make tag of IFD0 names Canon
+pub(crate) fn is_canon(exif_entries: &[ExifEntry]) -> bool {
+    exif_entries.iter().any(|e| {
+        e.ifd.ifd_kind == IfdKind::Ifd0
+            && e.tag == ExifTag::Make
+            && matches!(e.value, TagValue::Ascii(ref s) | TagValue::Utf8(ref s) if s.starts_with("Canon"))
+    })
+}
+
+/// Parses the MakerNote of Canon cameras, that is an IFD without header whose
+/// offsets are relative to the TIFF header, like those of standard IFDs. Its
+/// entries are put in `Namespace::Canon`. `length` is the size of the MakerNote,
+/// that must hold the whole directory.
+///
+/// Problems are reported as warnings, since the rest of the EXIF data is still good.
+pub(crate) fn parse_canon_makernote<S: TiffSource>(
+    le: bool,
+    contents: &mut S,
+    offset: usize,
+    length: usize,
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) {
+    let count = match contents.read(offset, 2) {
+        Some(count) => read_u16(le, &count) as usize,
+        None => {
+            warnings.push("Canon MakerNote truncated at dir entry count".to_string());
+            return;
+        }
+    };
+    if 2 + count * 12 > length {
+        warnings.push(format!(
+            "Canon MakerNote ignored: {} entries do not fit in {} bytes",
+            count, length
+        ));
+        return;
+    }
+
+    let ifd = contents
+        .read(offset + 2, count * 12)
+        .and_then(|ifd_content| {
+            parse_ifd(true, IfdKind::MakerNote, le, false, count, &ifd_content)
+        });
+    let (ifd, _) = match ifd {
+        Some(ifd) => ifd,
+        None => {
+            warnings.push("Canon MakerNote truncated at dir listing".to_string());
+            return;
+        }
+    };
+
+    for mut entry in ifd {
+        entry.namespace = Namespace::Canon;
+        if entry.copy_data_from(contents) {
+            exif_entries.push(parse_exif_entry(&entry, warnings));
+        }
+    }
+}
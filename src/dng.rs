@@ -0,0 +1,90 @@
+use super::types::*;
+
+/// Value of a tag found in the given IFD
+fn find(entries: &[ExifEntry], ifd_kind: IfdKind, tag: ExifTag) -> Option<&TagValue> {
+    entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == ifd_kind && e.tag == tag)
+        .map(|e| &e.value)
+}
+
+/// Values of a numeric tag, whatever its format
+fn numbers(value: &TagValue) -> Vec<f64> {
+    (0..).map_while(|i| value.to_f64(i)).collect()
+}
+
+fn text(value: &TagValue) -> Option<String> {
+    match *value {
+        TagValue::Ascii(ref s) | TagValue::Utf8(ref s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn version(value: &TagValue) -> Option<[u8; 4]> {
+    match *value {
+        TagValue::U8(ref v) if v.len() == 4 => Some([v[0], v[1], v[2], v[3]]),
+        _ => None,
+    }
+}
+
+fn pair(value: &TagValue) -> Option<[f64; 2]> {
+    Some([value.to_f64(0)?, value.to_f64(1)?])
+}
+
+/// IFD that holds the raw image data: the first of IFD0 and the sub-IFDs
+/// that holds a main image rather than a preview, according to NewSubfileType
+fn raw_ifd(entries: &[ExifEntry]) -> IfdKind {
+    let mut candidates = vec![IfdKind::Ifd0];
+    for entry in entries {
+        if let IfdKind::SubIfd(_) = entry.ifd.ifd_kind {
+            if !candidates.contains(&entry.ifd.ifd_kind) {
+                candidates.push(entry.ifd.ifd_kind);
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .find(|&ifd_kind| {
+            find(entries, ifd_kind, ExifTag::NewSubfileType).map_or(0, |v| v.to_i64(0).unwrap_or(0))
+                == 0
+        })
+        .unwrap_or(IfdKind::Ifd0)
+}
+
+/// Gathers the DNG tags of IFD0 and of the raw image IFD. Returns None if the
+/// entries do not come from a DNG file, i.e. lack DNGVersion.
+pub(crate) fn dng_info(entries: &[ExifEntry]) -> Option<DngInfo> {
+    let ifd0 = |tag: ExifTag| find(entries, IfdKind::Ifd0, tag);
+    let raw_ifd = raw_ifd(entries);
+    let raw = |tag: ExifTag| find(entries, raw_ifd, tag);
+    let illuminant = |tag: ExifTag| ifd0(tag).and_then(|v| v.to_i64(0)).map(|n| n as u16);
+
+    Some(DngInfo {
+        version: ifd0(ExifTag::DNGVersion).and_then(version)?,
+        backward_version: ifd0(ExifTag::DNGBackwardVersion).and_then(version),
+        unique_camera_model: ifd0(ExifTag::UniqueCameraModel).and_then(text),
+        profile_name: ifd0(ExifTag::ProfileName).and_then(text),
+        color_matrix1: ifd0(ExifTag::ColorMatrix1).map(numbers),
+        color_matrix2: ifd0(ExifTag::ColorMatrix2).map(numbers),
+        color_matrix3: ifd0(ExifTag::ColorMatrix3).map(numbers),
+        calibration_illuminant1: illuminant(ExifTag::CalibrationIlluminant1),
+        calibration_illuminant2: illuminant(ExifTag::CalibrationIlluminant2),
+        calibration_illuminant3: illuminant(ExifTag::CalibrationIlluminant3),
+        as_shot_neutral: ifd0(ExifTag::AsShotNeutral).map(numbers),
+        baseline_exposure: ifd0(ExifTag::BaselineExposure).and_then(|v| v.to_f64(0)),
+        raw_ifd,
+        active_area: raw(ExifTag::ActiveArea).and_then(|v| {
+            let n = |i: usize| v.to_i64(i).map(|n| n as u32);
+            Some([n(0)?, n(1)?, n(2)?, n(3)?])
+        }),
+        default_crop_origin: raw(ExifTag::DefaultCropOrigin).and_then(pair),
+        default_crop_size: raw(ExifTag::DefaultCropSize).and_then(pair),
+        black_level: raw(ExifTag::BlackLevel).map(numbers),
+        white_level: raw(ExifTag::WhiteLevel)
+            .map(|v| (0..).map_while(|i| v.to_i64(i)).map(|n| n as u32).collect()),
+        linearization_table: match raw(ExifTag::LinearizationTable) {
+            Some(TagValue::U16(ref v)) => Some(v.clone()),
+            _ => None,
+        },
+    })
+}
@@ -1,14 +1,129 @@
 use super::exifreadable::*;
 use super::types::*;
 
+/// Information about a tag: enumeration, unit, format, minimum and maximum count
+/// (-1 when the count is not fixed) and the function that makes the value readable.
+/// The format is Unknown for tags that may be of any numeric format.
+pub type TagInfo = (ExifTag, &'static str, IfdFormat, i32, i32, fn(&TagValue) -> String);
+
+static UNKNOWN_TAG: TagInfo =
+	(ExifTag::UnknownToMe, "Unknown unit", IfdFormat::Unknown, -1i32, -1i32, nop);
+
 /// Convert a numeric tag into ExifTag enumeration, and yields information about the tag. This information
 /// is used by the main body of the parser to sanity-check the tags found in image
-/// and make sure that EXIF tags have the right data types
-pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
-					           fn(&TagValue) -> String)
+/// and make sure that EXIF tags have the right data types.
+///
+/// The same tag number means different things in different namespaces, e.g. 0x0001
+/// is GPSLatitudeRef in the GPS IFD and InteroperabilityIndex in the Interoperability IFD.
+pub fn tag_to_exif(namespace: Namespace, f: u16) -> TagInfo
+{
+	match namespace {
+	Namespace::Standard => standard_tag_to_exif(f),
+	Namespace::Gps => gps_tag_to_exif(f),
+	Namespace::Interop => interop_tag_to_exif(f),
+	Namespace::Mpf => mpf_tag_to_exif(f),
+	Namespace::Canon => canon_tag_to_exif(f),
+	_ => UNKNOWN_TAG,
+	}
+}
+
+/// Tags of IFD0, Exif SubIFD and IFD1
+fn standard_tag_to_exif(f: u16) -> TagInfo
 {
 	match f {
 
+	0x00fe =>
+	(ExifTag::NewSubfileType, "none",
+	IfdFormat::U32, 1, 1, new_subfile_type),
+
+	0x0100 =>
+	(ExifTag::ImageWidth, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0101 =>
+	(ExifTag::ImageLength, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0102 =>
+	(ExifTag::BitsPerSample, "bits",
+	IfdFormat::U16, 1, i32::MAX, strpass),
+
+	0x0103 =>
+	(ExifTag::Compression, "none",
+	IfdFormat::U16, 1, 1, compression),
+
+	0x0106 =>
+	(ExifTag::PhotometricInterpretation, "none",
+	IfdFormat::U16, 1, 1, photometric_interpretation),
+
+	0x010d =>
+	(ExifTag::DocumentName, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x0111 =>
+	(ExifTag::StripOffsets, "byte offset", IfdFormat::U32,
+	1, i32::MAX, strpass),
+
+	0x0115 =>
+	(ExifTag::SamplesPerPixel, "none",
+	IfdFormat::U16, 1, 1, strpass),
+
+	0x0116 =>
+	(ExifTag::RowsPerStrip, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0117 =>
+	(ExifTag::StripByteCounts, "bytes", IfdFormat::U32,
+	1, i32::MAX, strpass),
+
+	0x011c =>
+	(ExifTag::PlanarConfiguration, "none",
+	IfdFormat::U16, 1, 1, planar_configuration),
+
+	0x013b =>
+	(ExifTag::Artist, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x013d =>
+	(ExifTag::Predictor, "none",
+	IfdFormat::U16, 1, 1, predictor),
+
+	0x0142 =>
+	(ExifTag::TileWidth, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0143 =>
+	(ExifTag::TileLength, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0144 =>
+	(ExifTag::TileOffsets, "byte offset", IfdFormat::U32,
+	1, i32::MAX, strpass),
+
+	0x0145 =>
+	(ExifTag::TileByteCounts, "bytes", IfdFormat::U32,
+	1, i32::MAX, strpass),
+
+	0x014a =>
+	(ExifTag::SubIFDs, "byte offset",
+	IfdFormat::U32, 1, i32::MAX, strpass),
+
+	0x0152 =>
+	(ExifTag::ExtraSamples, "none",
+	IfdFormat::U16, 1, i32::MAX, extra_samples),
+
+	0x0153 =>
+	(ExifTag::SampleFormat, "none",
+	IfdFormat::U16, 1, i32::MAX, sample_format),
+
+	0x0212 =>
+	(ExifTag::YCbCrSubSampling, "none",
+	IfdFormat::U16, 2, 2, ycbcr_subsampling),
+
+	0x0213 =>
+	(ExifTag::YCbCrPositioning, "none",
+	IfdFormat::U16, 1, 1, ycbcr_positioning),
+
 	0x010e =>
 	(ExifTag::ImageDescription, "none", IfdFormat::Ascii,
 	-1i32, -1i32, strpass),
@@ -54,10 +169,46 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::YCbCrCoefficients, "none",
 	IfdFormat::URational, 3, 3, rational_values),
 
+	0x0201 =>
+	(ExifTag::JPEGInterchangeFormat, "byte offset",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x0202 =>
+	(ExifTag::JPEGInterchangeFormatLength, "bytes",
+	IfdFormat::U32, 1, 1, strpass),
+
 	0x0214 =>
 	(ExifTag::ReferenceBlackWhite, "RGB or YCbCr",
 	IfdFormat::URational, 6, 6, rational_values),
 
+	0x4746 =>
+	(ExifTag::Rating, "stars",
+	IfdFormat::U16, 1, 1, rating),
+
+	0x4749 =>
+	(ExifTag::RatingPercent, "%",
+	IfdFormat::U16, 1, 1, rating_percent),
+
+	0x9c9b =>
+	(ExifTag::XPTitle, "none",
+	IfdFormat::U8, 1, i32::MAX, ucs2_string),
+
+	0x9c9c =>
+	(ExifTag::XPComment, "none",
+	IfdFormat::U8, 1, i32::MAX, ucs2_string),
+
+	0x9c9d =>
+	(ExifTag::XPAuthor, "none",
+	IfdFormat::U8, 1, i32::MAX, ucs2_string),
+
+	0x9c9e =>
+	(ExifTag::XPKeywords, "none",
+	IfdFormat::U8, 1, i32::MAX, ucs2_string),
+
+	0x9c9f =>
+	(ExifTag::XPSubject, "none",
+	IfdFormat::U8, 1, i32::MAX, ucs2_string),
+
 	0x8298 =>
 	(ExifTag::Copyright, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
 
@@ -93,6 +244,26 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::ISOSpeedRatings, "ISO",
 	IfdFormat::U16, 1, 3, iso_speeds),
 
+	0x8831 =>
+	(ExifTag::StandardOutputSensitivity, "ISO",
+	IfdFormat::U32, 1, 1, iso_speed),
+
+	0x8832 =>
+	(ExifTag::RecommendedExposureIndex, "ISO",
+	IfdFormat::U32, 1, 1, iso_speed),
+
+	0x8833 =>
+	(ExifTag::ISOSpeed, "ISO",
+	IfdFormat::U32, 1, 1, iso_speed),
+
+	0x8834 =>
+	(ExifTag::ISOSpeedLatitudeyyy, "ISO",
+	IfdFormat::U32, 1, 1, iso_speed),
+
+	0x8835 =>
+	(ExifTag::ISOSpeedLatitudezzz, "ISO",
+	IfdFormat::U32, 1, 1, iso_speed),
+
 	0x8828 =>
 	(ExifTag::OECF, "none",
 	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
@@ -109,6 +280,26 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::DateTimeDigitized, "none",
 	IfdFormat::Ascii, -1i32, -1i32, strpass),
 
+	0x9010 =>
+	(ExifTag::OffsetTime, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9011 =>
+	(ExifTag::OffsetTimeOriginal, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9012 =>
+	(ExifTag::OffsetTimeDigitized, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9101 =>
+	(ExifTag::ComponentsConfiguration, "none",
+	IfdFormat::Undefined, 4, 4, components_configuration),
+
+	0x9102 =>
+	(ExifTag::CompressedBitsPerPixel, "bits per pixel",
+	IfdFormat::URational, 1, 1, rational_value),
+
 	0x9201 =>
 	(ExifTag::ShutterSpeedValue, "APEX",
 	IfdFormat::IRational, 1, 1, apex_tv),
@@ -160,6 +351,42 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::UserComment, "none",
 	IfdFormat::Undefined, -1i32, -1i32, undefined_as_encoded_string),
 
+	0x9290 =>
+	(ExifTag::SubSecTime, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9291 =>
+	(ExifTag::SubSecTimeOriginal, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9292 =>
+	(ExifTag::SubSecTimeDigitized, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x9400 =>
+	(ExifTag::Temperature, "Celsius",
+	IfdFormat::IRational, 1, 1, temperature),
+
+	0x9401 =>
+	(ExifTag::Humidity, "%",
+	IfdFormat::URational, 1, 1, humidity),
+
+	0x9402 =>
+	(ExifTag::Pressure, "hPa",
+	IfdFormat::URational, 1, 1, pressure),
+
+	0x9403 =>
+	(ExifTag::WaterDepth, "m",
+	IfdFormat::IRational, 1, 1, water_depth),
+
+	0x9404 =>
+	(ExifTag::Acceleration, "mGal",
+	IfdFormat::URational, 1, 1, acceleration),
+
+	0x9405 =>
+	(ExifTag::CameraElevationAngle, "deg",
+	IfdFormat::IRational, 1, 1, camera_elevation_angle),
+
 	0xa000 =>
 	(ExifTag::FlashPixVersion, "none",
 	IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
@@ -172,6 +399,10 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::RelatedSoundFile, "none",
 	IfdFormat::Ascii, -1i32, -1i32, strpass),
 
+	0xa005 =>
+	(ExifTag::InteroperabilityOffset, "byte offset",
+	IfdFormat::U32, 1, 1, strpass),
+
 	0xa20b => (ExifTag::FlashEnergy, "BCPS",
 	IfdFormat::URational, 1, 1, flash_energy),
 
@@ -280,7 +511,225 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	0xa420 =>
 	(ExifTag::ImageUniqueID, "none",
 	IfdFormat::Ascii, -1i32, -1i32, strpass),
-		
+
+	0xa430 =>
+	(ExifTag::CameraOwnerName, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa431 =>
+	(ExifTag::BodySerialNumber, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa435 =>
+	(ExifTag::LensSerialNumber, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa436 =>
+	(ExifTag::ImageTitle, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa437 =>
+	(ExifTag::Photographer, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa438 =>
+	(ExifTag::ImageEditor, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa439 =>
+	(ExifTag::CameraFirmware, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa43a =>
+	(ExifTag::RAWDevelopingSoftware, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa43b =>
+	(ExifTag::ImageEditingSoftware, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa43c =>
+	(ExifTag::MetadataEditingSoftware, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xa460 =>
+	(ExifTag::CompositeImage, "none",
+	IfdFormat::U16, 1, 1, composite_image),
+
+	0xa461 =>
+	(ExifTag::SourceImageNumberOfCompositeImage, "none",
+	IfdFormat::U16, 2, 2, source_image_number),
+
+	0xa462 =>
+	(ExifTag::SourceExposureTimesOfCompositeImage, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xc612 =>
+	(ExifTag::DNGVersion, "none",
+	IfdFormat::U8, 4, 4, dng_version),
+
+	0xc613 =>
+	(ExifTag::DNGBackwardVersion, "none",
+	IfdFormat::U8, 4, 4, dng_version),
+
+	0xc614 =>
+	(ExifTag::UniqueCameraModel, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xc618 =>
+	(ExifTag::LinearizationTable, "none",
+	IfdFormat::U16, 1, i32::MAX, numeric_table),
+
+	0xc619 =>
+	(ExifTag::BlackLevelRepeatDim, "none",
+	IfdFormat::U16, 2, 2, strpass),
+
+	0xc61a =>
+	(ExifTag::BlackLevel, "none",
+	IfdFormat::Unknown, 1, i32::MAX, numeric_values),
+
+	0xc61d =>
+	(ExifTag::WhiteLevel, "none",
+	IfdFormat::U32, 1, i32::MAX, strpass),
+
+	0xc61e =>
+	(ExifTag::DefaultScale, "none",
+	IfdFormat::URational, 2, 2, rational_values),
+
+	0xc61f =>
+	(ExifTag::DefaultCropOrigin, "pixels",
+	IfdFormat::Unknown, 2, 2, numeric_values),
+
+	0xc620 =>
+	(ExifTag::DefaultCropSize, "pixels",
+	IfdFormat::Unknown, 2, 2, numeric_values),
+
+	0xc621 =>
+	(ExifTag::ColorMatrix1, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xc622 =>
+	(ExifTag::ColorMatrix2, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xc623 =>
+	(ExifTag::CameraCalibration1, "none",
+	IfdFormat::IRational, 1, i32::MAX, signed_rational_values),
+
+	0xc624 =>
+	(ExifTag::CameraCalibration2, "none",
+	IfdFormat::IRational, 1, i32::MAX, signed_rational_values),
+
+	0xc627 =>
+	(ExifTag::AnalogBalance, "none",
+	IfdFormat::URational, 1, i32::MAX, rational_values),
+
+	0xc628 =>
+	(ExifTag::AsShotNeutral, "none",
+	IfdFormat::Unknown, 1, i32::MAX, numeric_values),
+
+	0xc629 =>
+	(ExifTag::AsShotWhiteXY, "CIE 1931 coordinates",
+	IfdFormat::URational, 2, 2, rational_values),
+
+	0xc62a =>
+	(ExifTag::BaselineExposure, "EV",
+	IfdFormat::IRational, 1, 1, ev_offset),
+
+	0xc62f =>
+	(ExifTag::CameraSerialNumber, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xc630 =>
+	(ExifTag::LensInfo, "none",
+	IfdFormat::URational, 4, 4, lens_spec),
+
+	0xc634 =>
+	(ExifTag::DNGPrivateData, "none",
+	IfdFormat::U8, 1, i32::MAX, undefined_as_blob),
+
+	0xc65a =>
+	(ExifTag::CalibrationIlluminant1, "none",
+	IfdFormat::U16, 1, 1, light_source),
+
+	0xc65b =>
+	(ExifTag::CalibrationIlluminant2, "none",
+	IfdFormat::U16, 1, 1, light_source),
+
+	0xc68d =>
+	(ExifTag::ActiveArea, "pixels",
+	IfdFormat::U32, 4, 4, strpass),
+
+	0xc6f8 =>
+	(ExifTag::ProfileName, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xc6fd =>
+	(ExifTag::ProfileEmbedPolicy, "none",
+	IfdFormat::U32, 1, 1, profile_embed_policy),
+
+	0xc6fe =>
+	(ExifTag::ProfileCopyright, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0xc714 =>
+	(ExifTag::ForwardMatrix1, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xc715 =>
+	(ExifTag::ForwardMatrix2, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xc740 =>
+	(ExifTag::OpcodeList1, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xc741 =>
+	(ExifTag::OpcodeList2, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xc74e =>
+	(ExifTag::OpcodeList3, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xcd31 =>
+	(ExifTag::CalibrationIlluminant3, "none",
+	IfdFormat::U16, 1, 1, light_source),
+
+	0xcd32 =>
+	(ExifTag::CameraCalibration3, "none",
+	IfdFormat::IRational, 1, i32::MAX, signed_rational_values),
+
+	0xcd33 =>
+	(ExifTag::ColorMatrix3, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xcd34 =>
+	(ExifTag::ForwardMatrix3, "none",
+	IfdFormat::IRational, 3, i32::MAX, signed_rational_values),
+
+	0xcd35 =>
+	(ExifTag::IlluminantData1, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xcd36 =>
+	(ExifTag::IlluminantData2, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xcd37 =>
+	(ExifTag::IlluminantData3, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	_ => UNKNOWN_TAG,
+
+	}
+}
+
+/// Tags of the GPS IFD
+fn gps_tag_to_exif(f: u16) -> TagInfo
+{
+	match f {
+
 	0x0 =>
 	(ExifTag::GPSVersionID, "none",
 	IfdFormat::U8, 4, 4, strpass),
@@ -401,9 +850,192 @@ pub fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32,
 	(ExifTag::GPSDifferential, "none",
 	IfdFormat::U16, 1, 1, gpsdiff),
 
-	_ =>
-	(ExifTag::UnknownToMe, "Unknown unit",
-	IfdFormat::Unknown, -1i32, -1i32, nop)
+	_ => UNKNOWN_TAG,
+
+	}
+}
+
+/// Tags of the Interoperability IFD
+fn interop_tag_to_exif(f: u16) -> TagInfo
+{
+	match f {
+
+	0x1 =>
+	(ExifTag::InteroperabilityIndex, "none",
+	IfdFormat::Ascii, -1i32, -1i32, interoperability_index),
+
+	0x2 =>
+	(ExifTag::InteroperabilityVersion, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+	0x1000 =>
+	(ExifTag::RelatedImageFileFormat, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	// SHORT or LONG
+	0x1001 =>
+	(ExifTag::RelatedImageWidth, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0x1002 =>
+	(ExifTag::RelatedImageLength, "pixels",
+	IfdFormat::U32, 1, 1, strpass),
+
+	_ => UNKNOWN_TAG,
+
+	}
+}
+
+/// Tags of the MP Index IFD and the MP Attribute IFDs of Multi-Picture Format
+fn mpf_tag_to_exif(f: u16) -> TagInfo
+{
+	match f {
+
+	0xb000 =>
+	(ExifTag::MPFVersion, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+	0xb001 =>
+	(ExifTag::NumberOfImages, "none",
+	IfdFormat::U32, 1, 1, strpass),
+
+	// 16 bytes for each image
+	0xb002 =>
+	(ExifTag::MPEntry, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xb003 =>
+	(ExifTag::ImageUIDList, "none",
+	IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+	0xb004 =>
+	(ExifTag::TotalFrames, "none",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0xb101 =>
+	(ExifTag::MPIndividualNum, "none",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0xb201 =>
+	(ExifTag::PanOrientation, "none",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0xb202 =>
+	(ExifTag::PanOverlapH, "%",
+	IfdFormat::URational, 1, 1, mp_overlap),
+
+	0xb203 =>
+	(ExifTag::PanOverlapV, "%",
+	IfdFormat::URational, 1, 1, mp_overlap),
+
+	0xb204 =>
+	(ExifTag::BaseViewpointNum, "none",
+	IfdFormat::U32, 1, 1, strpass),
+
+	0xb205 =>
+	(ExifTag::ConvergenceAngle, "deg",
+	IfdFormat::IRational, 1, 1, mp_angle),
+
+	0xb206 =>
+	(ExifTag::BaselineLength, "m",
+	IfdFormat::URational, 1, 1, mp_distance),
+
+	0xb207 =>
+	(ExifTag::VerticalDivergence, "deg",
+	IfdFormat::IRational, 1, 1, mp_angle),
+
+	0xb208 =>
+	(ExifTag::AxisDistanceX, "m",
+	IfdFormat::IRational, 1, 1, mp_distance),
+
+	0xb209 =>
+	(ExifTag::AxisDistanceY, "m",
+	IfdFormat::IRational, 1, 1, mp_distance),
+
+	0xb20a =>
+	(ExifTag::AxisDistanceZ, "m",
+	IfdFormat::IRational, 1, 1, mp_distance),
+
+	0xb20b =>
+	(ExifTag::YawAngle, "deg",
+	IfdFormat::IRational, 1, 1, mp_angle),
+
+	0xb20c =>
+	(ExifTag::PitchAngle, "deg",
+	IfdFormat::IRational, 1, 1, mp_angle),
+
+	0xb20d =>
+	(ExifTag::RollAngle, "deg",
+	IfdFormat::IRational, 1, 1, mp_angle),
+
+	_ => UNKNOWN_TAG,
+
+	}
+}
+
+/// Tags of the MakerNote IFD of Canon cameras. Most of them are arrays of 16-bit
+/// values, whose items are decoded by the readable functions.
+fn canon_tag_to_exif(f: u16) -> TagInfo
+{
+	match f {
+
+	0x0001 =>
+	(ExifTag::CanonCameraSettings, "none",
+	IfdFormat::U16, 1, i32::MAX, canon_camera_settings),
+
+	0x0002 =>
+	(ExifTag::CanonFocalLength, "mm",
+	IfdFormat::U16, 2, i32::MAX, canon_focal_length),
+
+	0x0004 =>
+	(ExifTag::CanonShotInfo, "none",
+	IfdFormat::U16, 1, i32::MAX, canon_shot_info),
+
+	0x0006 =>
+	(ExifTag::CanonImageType, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x0007 =>
+	(ExifTag::CanonFirmwareVersion, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x0008 =>
+	(ExifTag::CanonFileNumber, "none",
+	IfdFormat::U32, 1, 1, canon_file_number),
+
+	0x0009 =>
+	(ExifTag::CanonOwnerName, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x000c =>
+	(ExifTag::CanonSerialNumber, "none",
+	IfdFormat::U32, 1, 1, canon_serial_number),
+
+	0x0010 =>
+	(ExifTag::CanonModelID, "none",
+	IfdFormat::U32, 1, 1, canon_model_id),
+
+	0x0012 =>
+	(ExifTag::CanonAFInfo, "none",
+	IfdFormat::U16, 8, i32::MAX, canon_af_info),
+
+	0x0026 =>
+	(ExifTag::CanonAFInfo2, "none",
+	IfdFormat::U16, 8, i32::MAX, canon_af_info2),
+
+	0x0093 =>
+	(ExifTag::CanonFileInfo, "none",
+	IfdFormat::U16, 1, i32::MAX, canon_file_info),
+
+	0x0095 =>
+	(ExifTag::CanonLensModel, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	0x0096 =>
+	(ExifTag::CanonInternalSerialNumber, "none",
+	IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+	_ => UNKNOWN_TAG,
 
 	}
 }
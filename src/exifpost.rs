@@ -1,3 +1,4 @@
+use super::exifreadable::canon_file_info_count;
 use super::types::*;
 
 /// Find a tag of given type
@@ -86,6 +87,34 @@ pub fn exif_postprocessing(entry: &mut ExifEntry, entries: &[ExifEntry]) {
                 entry.value_more_readable.push_str(&f.value_more_readable);
             }
         }
+
+        ExifTag::CanonFocalLength => {
+            // focal length is in units of 1/FocalUnits mm, FocalUnits being in CameraSettings
+            let units = other_tag(ExifTag::CanonCameraSettings, entries)
+                .and_then(|f| f.value.to_i64(25))
+                .filter(|&units| units > 1);
+            if let (Some(units), Some(focal)) = (units, entry.value.to_i64(1)) {
+                entry.value_more_readable = format!("{} mm", focal as f64 / units as f64);
+            }
+        }
+        ExifTag::CanonFileInfo => {
+            // item 1 is decoded according to the camera model
+            let model = other_tag(ExifTag::Model, entries).and_then(|f| match f.value {
+                TagValue::Ascii(ref model) => Some(model.as_str()),
+                _ => None,
+            });
+            let count = match (&entry.value, model) {
+                (TagValue::U16(ref v), Some(model)) => canon_file_info_count(v, model),
+                _ => None,
+            };
+            if let Some((name, value)) = count {
+                if !entry.value_more_readable.is_empty() {
+                    entry.value_more_readable.push_str(", ");
+                }
+                let item = format!("{}: {}", name, value);
+                entry.value_more_readable.push_str(&item);
+            }
+        }
         _ => (),
     }
 }
@@ -71,6 +71,30 @@ pub fn rational_values(e: &TagValue) -> String {
     }
 }
 
+pub fn signed_rational_values(e: &TagValue) -> String {
+    match *e {
+        TagValue::IRational(ref v) => {
+            let ve: Vec<f64> = v.iter().map(|&x| x.value()).collect();
+            numarray_to_string(&ve)
+        }
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Values of a tag that may be of any numeric format
+pub fn numeric_values(e: &TagValue) -> String {
+    let ve: Vec<f64> = (0..).map_while(|i| e.to_f64(i)).collect();
+    numarray_to_string(&ve)
+}
+
+/// Prints a lookup table simply as its size, since it may have thousands of entries
+pub fn numeric_table(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => format!("Table of {} values", v.len()),
+        _ => panic!("{}", INV),
+    }
+}
+
 pub fn resolution_unit(e: &TagValue) -> String {
     match *e {
         TagValue::U16(ref v) => {
@@ -87,6 +111,186 @@ pub fn resolution_unit(e: &TagValue) -> String {
     .to_string()
 }
 
+/// Kind of image described by the IFD, as a set of flags. Expected to be LONG,
+/// but SHORT is accepted as well.
+pub fn new_subfile_type(e: &TagValue) -> String {
+    let n = match e.to_i64(0) {
+        Some(n) => n,
+        None => panic!("{}", INV),
+    };
+    if n == 0 {
+        return "Full-resolution image".to_string();
+    }
+    let flags: Vec<&str> = [
+        (1, "Reduced-resolution image"),
+        (2, "Page of multi-page image"),
+        (4, "Transparency mask"),
+        (16, "MRC imaging model"),
+    ]
+    .iter()
+    .filter(|&&(bit, _)| n & bit != 0)
+    .map(|&(_, name)| name)
+    .collect();
+    if flags.is_empty() {
+        format!("Unknown ({})", n)
+    } else {
+        flags.join(", ")
+    }
+}
+
+pub fn compression(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                1 => "Uncompressed",
+                2 => "CCITT modified Huffman RLE",
+                3 => "CCITT Group 3 fax",
+                4 => "CCITT Group 4 fax",
+                5 => "LZW",
+                6 => "JPEG (old-style)",
+                7 => "JPEG",
+                8 => "Deflate",
+                32773 => "PackBits",
+                32946 => "Deflate (PKZIP)",
+                34712 => "JPEG 2000",
+                34887 => "LERC",
+                34892 => "Lossy JPEG",
+                34925 => "LZMA",
+                50000 => "Zstandard",
+                50001 => "WebP",
+                52546 => "JPEG XL",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+pub fn photometric_interpretation(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                0 => "WhiteIsZero",
+                1 => "BlackIsZero",
+                2 => "RGB",
+                3 => "Palette color",
+                4 => "Transparency mask",
+                5 => "CMYK",
+                6 => "YCbCr",
+                8 => "CIE L*a*b*",
+                9 => "ICC L*a*b*",
+                10 => "ITU L*a*b*",
+                32803 => "Color filter array",
+                34892 => "Linear raw",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+pub fn planar_configuration(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                1 => "Chunky",
+                2 => "Planar",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+pub fn predictor(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                1 => "None",
+                2 => "Horizontal differencing",
+                3 => "Floating point",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+/// Meaning of the samples that follow the color channels, one per sample
+pub fn extra_samples(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => v
+            .iter()
+            .map(|&n| match n {
+                0 => "Unspecified".to_string(),
+                1 => "Associated alpha".to_string(),
+                2 => "Unassociated alpha".to_string(),
+                _ => format!("Unknown ({})", n),
+            })
+            .collect::<Vec<String>>()
+            .join(", "),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Data type of the samples, given once per sample, but usually the same for all
+pub fn sample_format(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => v
+            .iter()
+            .map(|&n| match n {
+                1 => "Unsigned integer".to_string(),
+                2 => "Signed integer".to_string(),
+                3 => "Floating point".to_string(),
+                4 => "Undefined".to_string(),
+                _ => format!("Unknown ({})", n),
+            })
+            .collect::<Vec<String>>()
+            .join(", "),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Subsampling of the chroma channels, horizontally and vertically, given in
+/// the usual J:a:b notation when possible
+pub fn ycbcr_subsampling(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => match (v[0], v[1]) {
+            (1, 1) => "4:4:4".to_string(),
+            (2, 1) => "4:2:2".to_string(),
+            (2, 2) => "4:2:0".to_string(),
+            (4, 1) => "4:1:1".to_string(),
+            (4, 2) => "4:1:0".to_string(),
+            (1, 2) => "4:4:0".to_string(),
+            (h, v) => format!("Unknown ({}x{})", h, v),
+        },
+        _ => panic!("{}", INV),
+    }
+}
+
+pub fn ycbcr_positioning(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                1 => "Centered",
+                2 => "Co-sited",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
 pub fn exposure_time(e: &TagValue) -> String {
     match *e {
         TagValue::URational(ref v) => {
@@ -170,6 +374,15 @@ pub fn iso_speeds(e: &TagValue) -> String {
     }
 }
 
+/// Sensitivity given as a single LONG value, as the tags of ISO 12232 that
+/// were added in Exif 2.3. SHORT is accepted as well.
+pub fn iso_speed(e: &TagValue) -> String {
+    match e.to_i64(0) {
+        Some(n) => format!("ISO {}", n),
+        None => panic!("{}", INV),
+    }
+}
+
 pub fn dms(e: &TagValue) -> String {
     match *e {
         TagValue::URational(ref v) => {
@@ -209,7 +422,7 @@ pub fn gps_alt_ref(e: &TagValue) -> String {
 
 pub fn gpsdestdistanceref(e: &TagValue) -> String {
     match *e {
-        TagValue::Ascii(ref v) => {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => {
             if v == "N" {
                 "kn"
             } else if v == "K" {
@@ -234,7 +447,7 @@ pub fn gpsdestdistance(e: &TagValue) -> String {
 
 pub fn gpsspeedref(e: &TagValue) -> String {
     match *e {
-        TagValue::Ascii(ref v) => {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => {
             if v == "N" {
                 "kn"
             } else if v == "K" {
@@ -259,7 +472,7 @@ pub fn gpsspeed(e: &TagValue) -> String {
 
 pub fn gpsbearingref(e: &TagValue) -> String {
     match *e {
-        TagValue::Ascii(ref v) => {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => {
             if v == "T" {
                 "True bearing"
             } else if v == "M" {
@@ -314,7 +527,7 @@ pub fn gpsdiff(e: &TagValue) -> String {
 
 pub fn gpsstatus(e: &TagValue) -> String {
     match *e {
-        TagValue::Ascii(ref v) => {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => {
             if v == "A" {
                 "Measurement in progress"
             } else if v == "V" {
@@ -330,7 +543,7 @@ pub fn gpsstatus(e: &TagValue) -> String {
 
 pub fn gpsmeasuremode(e: &TagValue) -> String {
     match *e {
-        TagValue::Ascii(ref v) => {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => {
             if v == "2" {
                 "2-dimension"
             } else if v == "3" {
@@ -344,6 +557,47 @@ pub fn gpsmeasuremode(e: &TagValue) -> String {
     .to_string()
 }
 
+/// Interoperability rule of the image. For DCF images, tells whether the
+/// color space is sRGB (R98) or Adobe RGB (R03).
+pub fn interoperability_index(e: &TagValue) -> String {
+    match *e {
+        TagValue::Ascii(ref v) | TagValue::Utf8(ref v) => match &v[..] {
+            "R98" => "R98 (DCF basic file, sRGB)",
+            "THM" => "THM (DCF thumbnail file)",
+            "R03" => "R03 (DCF option file, Adobe RGB)",
+            _ => return format!("Unknown ({})", v),
+        },
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+/// Overlap of the images of a panorama, as a percentage of the image size
+pub fn mp_overlap(e: &TagValue) -> String {
+    match *e {
+        TagValue::URational(ref v) => format!("{:.1}%", v[0].value()),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Angle between the viewpoints of a multi-view image
+pub fn mp_angle(e: &TagValue) -> String {
+    match *e {
+        TagValue::IRational(ref v) => format!("{:.2}°", v[0].value()),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Distance between the viewpoints of a multi-view image. Stereo baselines are
+/// a few centimeters, so the precision of `meters()` would not do.
+pub fn mp_distance(e: &TagValue) -> String {
+    match *e {
+        TagValue::URational(ref v) => format!("{:.3} m", v[0].value()),
+        TagValue::IRational(ref v) => format!("{:.3} m", v[0].value()),
+        _ => panic!("{}", INV),
+    }
+}
+
 /// Interprets an Undefined tag as ASCII, when the contents are guaranteed
 /// by EXIF standard to be ASCII-compatible. This function accepts UTF-8
 /// strings, should they be accepted by EXIF standard in the future.
@@ -400,10 +654,52 @@ pub fn undefined_as_encoded_string(e: &TagValue) -> String {
     }
 }
 
+/// Decodes the text of the tags written by Windows Explorer, that are stored as
+/// bytes but contain a UCS-2 string, always in little-endian order. The string
+/// ends at the first NUL, if any, and a trailing odd byte is ignored.
+pub fn ucs2_text(v: &[u8]) -> String {
+    let units = read_u16_array(true, (v.len() / 2) as u32, v);
+    let end = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+    String::from_utf16_lossy(&units[..end])
+}
+
+/// Text tags written by Windows Explorer, whose value is decoded by the parser
+pub fn ucs2_string(e: &TagValue) -> String {
+    match *e {
+        TagValue::Utf8(ref s) => s.clone(),
+        TagValue::U8(ref v) => ucs2_text(v),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Star rating set in Windows Explorer, from 1 to 5, or 0 when not rated
+pub fn rating(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => match v[0] {
+            0 => "Not rated".to_string(),
+            1 => "1 star".to_string(),
+            n @ 2..=5 => format!("{} stars", n),
+            n => format!("Unknown ({})", n),
+        },
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Star rating set in Windows Explorer, as a percentage: 1, 25, 50, 75 and
+/// 99 for 1 to 5 stars
+pub fn rating_percent(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => format!("{}%", v[0]),
+        _ => panic!("{}", INV),
+    }
+}
+
 /// Prints an opaque and long Undefined tag simply as as "blob", noting its length
 pub fn undefined_as_blob(e: &TagValue) -> String {
     match *e {
-        TagValue::Undefined(ref v, _) => format!("Blob of {} bytes", v.len()),
+        TagValue::Undefined(ref v, _) | TagValue::U8(ref v) => {
+            format!("Blob of {} bytes", v.len())
+        }
         _ => panic!(INV),
     }
 }
@@ -443,6 +739,38 @@ pub fn apex_ev(e: &TagValue) -> String {
     }
 }
 
+/// Exposure adjustment, in EV, that is not an APEX value
+pub fn ev_offset(e: &TagValue) -> String {
+    match *e {
+        TagValue::IRational(ref v) => format!("{:+.2} EV", v[0].value()),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Version of the DNG specification, e.g. 1.4.0.0
+pub fn dng_version(e: &TagValue) -> String {
+    match *e {
+        TagValue::U8(ref v) => format!("{}.{}.{}.{}", v[0], v[1], v[2], v[3]),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Whether the camera profile of a DNG file may be copied to other files
+pub fn profile_embed_policy(e: &TagValue) -> String {
+    let n = match e.to_i64(0) {
+        Some(n) => n,
+        None => panic!("{}", INV),
+    };
+    match n {
+        0 => "Allow copying",
+        1 => "Embed if used",
+        2 => "Never embed",
+        3 => "No restrictions",
+        _ => return format!("Unknown ({})", n),
+    }
+    .to_string()
+}
+
 pub fn file_source(e: &TagValue) -> String {
     match *e {
         TagValue::Undefined(ref v, _) => {
@@ -782,6 +1110,117 @@ pub fn subject_distance_range(e: &TagValue) -> String {
     .to_string()
 }
 
+/// Channels of each pixel of compressed data, in order. A value of 0 means
+/// that the channel does not exist.
+pub fn components_configuration(e: &TagValue) -> String {
+    match *e {
+        TagValue::Undefined(ref v, _) => v
+            .iter()
+            .filter(|&&c| c != 0)
+            .map(|&c| match c {
+                1 => "Y".to_string(),
+                2 => "Cb".to_string(),
+                3 => "Cr".to_string(),
+                4 => "R".to_string(),
+                5 => "G".to_string(),
+                6 => "B".to_string(),
+                _ => format!("Unknown ({})", c),
+            })
+            .collect::<Vec<String>>()
+            .join(""),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// Value of the measurements of the shooting conditions, added in Exif 2.31.
+/// A numerator or denominator of 0xFFFFFFFF means that the value is unknown.
+fn measurement(e: &TagValue) -> Option<f64> {
+    match *e {
+        TagValue::URational(ref v) => {
+            if v[0].numerator == 0xffff_ffff || v[0].denominator == 0xffff_ffff {
+                None
+            } else {
+                Some(v[0].value())
+            }
+        }
+        TagValue::IRational(ref v) => {
+            if v[0].numerator == -1 || v[0].denominator == -1 {
+                None
+            } else {
+                Some(v[0].value())
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+}
+
+pub fn temperature(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(t) => format!("{:.1} °C", t),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn humidity(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(h) => format!("{:.1}%", h),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn pressure(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(p) => format!("{:.1} hPa", p),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn water_depth(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(d) => format!("{:.2} m", d),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn acceleration(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(a) => format!("{:.1} mGal", a),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn camera_elevation_angle(e: &TagValue) -> String {
+    match measurement(e) {
+        Some(a) => format!("{:.1}°", a),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn composite_image(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let n = v[0];
+            match n {
+                0 => "Unknown",
+                1 => "Not a composite image",
+                2 => "General composite image",
+                3 => "Composite image captured when shooting",
+                _ => return format!("Unknown ({})", n),
+            }
+        }
+        _ => panic!("{}", INV),
+    }
+    .to_string()
+}
+
+/// Number of images captured for a composite image, and of those actually used
+pub fn source_image_number(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => format!("{} images, {} used", v[0], v[1]),
+        _ => panic!("{}", INV),
+    }
+}
+
 pub fn lens_spec(e: &TagValue) -> String {
     match *e {
         TagValue::URational(ref v) => {
@@ -806,3 +1245,389 @@ pub fn lens_spec(e: &TagValue) -> String {
         _ => panic!(INV),
     }
 }
+
+/// Item of an array of the Canon MakerNote, or None if it is missing or not
+/// applicable (-1). Items are signed, and the first one is the array size.
+fn canon_item(v: &[u16], index: usize) -> Option<i16> {
+    v.get(index).map(|&n| n as i16).filter(|&n| n != -1)
+}
+
+fn canon_name(n: i16, names: &[(i16, &str)]) -> String {
+    match names.iter().find(|&&(k, _)| k == n) {
+        Some(&(_, name)) => name.to_string(),
+        None => format!("Unknown ({})", n),
+    }
+}
+
+/// Joins the decoded items of an array of the Canon MakerNote, skipping
+/// those that are missing
+fn canon_fields(fields: Vec<(&str, Option<String>)>) -> String {
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| format!("{}: {}", name, v)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Exposure value of the Canon MakerNote, in 1/32 EV, where 12 and 20 stand
+/// for 1/3 and 2/3 EV
+fn canon_ev(n: i16) -> f64 {
+    let sign = if n < 0 { -1.0 } else { 1.0 };
+    let n = (n as i32).abs();
+    let fraction = match n & 0x1f {
+        0x0c => 32.0 / 3.0,
+        0x14 => 64.0 / 3.0,
+        f => f as f64,
+    };
+    sign * ((n & !0x1f) as f64 + fraction) / 32.0
+}
+
+static CANON_QUALITY: &[(i16, &str)] = &[
+    (1, "Economy"),
+    (2, "Normal"),
+    (3, "Fine"),
+    (4, "RAW"),
+    (5, "Superfine"),
+    (7, "CRAW"),
+];
+
+/// CameraSettings array: shooting modes and the focal range of the lens
+pub fn canon_camera_settings(e: &TagValue) -> String {
+    let v = match *e {
+        TagValue::U16(ref v) => v,
+        _ => panic!("{}", INV),
+    };
+
+    let macro_mode = canon_item(v, 1).map(|n| canon_name(n, &[(1, "Macro"), (2, "Normal")]));
+    let quality = canon_item(v, 3).map(|n| canon_name(n, CANON_QUALITY));
+    let flash_mode = canon_item(v, 4).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "Off"),
+                (1, "Auto"),
+                (2, "On"),
+                (3, "Red-eye reduction"),
+                (4, "Slow-sync"),
+                (5, "Red-eye reduction (Auto)"),
+                (6, "Red-eye reduction (On)"),
+                (16, "External flash"),
+            ],
+        )
+    });
+    let drive_mode = canon_item(v, 5).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "Single"),
+                (1, "Continuous"),
+                (2, "Movie"),
+                (3, "Continuous, speed priority"),
+                (4, "Continuous, low"),
+                (5, "Continuous, high"),
+                (6, "Silent single"),
+            ],
+        )
+    });
+    let focus_mode = canon_item(v, 7).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "One-shot AF"),
+                (1, "AI Servo AF"),
+                (2, "AI Focus AF"),
+                (3, "Manual focus"),
+                (4, "Single"),
+                (5, "Continuous"),
+                (6, "Manual focus"),
+                (16, "Pan focus"),
+            ],
+        )
+    });
+    let metering_mode = canon_item(v, 17).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "Default"),
+                (1, "Spot"),
+                (2, "Average"),
+                (3, "Evaluative"),
+                (4, "Partial"),
+                (5, "Center-weighted average"),
+            ],
+        )
+    });
+    let exposure_mode = canon_item(v, 20).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "Easy"),
+                (1, "Program AE"),
+                (2, "Shutter speed priority AE"),
+                (3, "Aperture-priority AE"),
+                (4, "Manual"),
+                (5, "Depth-of-field AE"),
+                (6, "M-Dep"),
+                (7, "Bulb"),
+                (8, "Flexible-priority AE"),
+            ],
+        )
+    });
+    let lens_type = canon_item(v, 22)
+        .filter(|&n| n != 0)
+        .map(|n| (n as u16).to_string());
+
+    // focal lengths are given in focal units per mm
+    let lens = match (v.get(23), v.get(24), canon_item(v, 25)) {
+        (Some(&max), Some(&min), Some(units)) if units > 0 && min > 0 => {
+            let max = max as f64 / units as f64;
+            let min = min as f64 / units as f64;
+            if max == min {
+                Some(format!("{} mm", min))
+            } else {
+                Some(format!("{}-{} mm", min, max))
+            }
+        }
+        _ => None,
+    };
+
+    canon_fields(vec![
+        ("Macro mode", macro_mode),
+        ("Quality", quality),
+        ("Flash mode", flash_mode),
+        ("Drive mode", drive_mode),
+        ("Focus mode", focus_mode),
+        ("Metering mode", metering_mode),
+        ("Exposure mode", exposure_mode),
+        ("Lens type", lens_type),
+        ("Lens", lens),
+    ])
+}
+
+/// FocalLength array: focal type, then the focal length in focal units per mm,
+/// that are given by CameraSettings, see `exif_postprocessing()`
+pub fn canon_focal_length(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => format!("{} mm", v[1]),
+        _ => panic!("{}", INV),
+    }
+}
+
+/// ShotInfo array: exposure chosen by the camera, white balance and the
+/// position of the image within a burst
+pub fn canon_shot_info(e: &TagValue) -> String {
+    let v = match *e {
+        TagValue::U16(ref v) => v,
+        _ => panic!("{}", INV),
+    };
+
+    let aperture = canon_item(v, 4).filter(|&n| n > 0).map(|n| {
+        format!(
+            "f/{:.1}",
+            (canon_ev(n) * std::f64::consts::LN_2 / 2.0).exp()
+        )
+    });
+    let exposure_time = canon_item(v, 5).filter(|&n| n > -1000).map(|n| {
+        let t = (-canon_ev(n) * std::f64::consts::LN_2).exp();
+        if t < 0.25 {
+            format!("1/{:.0} s", 1.0 / t)
+        } else {
+            format!("{:.1} s", t)
+        }
+    });
+    let exposure_compensation = canon_item(v, 6).map(|n| format!("{:+.1} EV", canon_ev(n)));
+    let white_balance = canon_item(v, 7).map(|n| {
+        canon_name(
+            n,
+            &[
+                (0, "Auto"),
+                (1, "Daylight"),
+                (2, "Cloudy"),
+                (3, "Tungsten"),
+                (4, "Fluorescent"),
+                (5, "Flash"),
+                (6, "Custom"),
+                (7, "Black & white"),
+                (8, "Shade"),
+                (9, "Manual temperature"),
+                (14, "Daylight fluorescent"),
+                (17, "Under water"),
+            ],
+        )
+    });
+    let sequence_number = canon_item(v, 9).map(|n| n.to_string());
+
+    canon_fields(vec![
+        ("Target aperture", aperture),
+        ("Target exposure time", exposure_time),
+        ("Exposure compensation", exposure_compensation),
+        ("White balance", white_balance),
+        ("Sequence number", sequence_number),
+    ])
+}
+
+/// FileInfo array: bracketing and RAW+JPEG settings. Item 1, the file number or
+/// shutter count, depends on the camera model and is added by the post-processing.
+pub fn canon_file_info(e: &TagValue) -> String {
+    let v = match *e {
+        TagValue::U16(ref v) => v,
+        _ => panic!("{}", INV),
+    };
+
+    let bracket_mode = canon_item(v, 3).map(|n| {
+        canon_name(
+            n,
+            &[(0, "Off"), (1, "AEB"), (2, "FEB"), (3, "ISO"), (4, "WB")],
+        )
+    });
+    let bracket_shot = canon_item(v, 5).map(|n| n.to_string());
+    let raw_jpeg_quality = canon_item(v, 6).map(|n| canon_name(n, CANON_QUALITY));
+    let live_view = canon_item(v, 19).map(|n| canon_name(n, &[(0, "Off"), (1, "On")]));
+
+    canon_fields(vec![
+        ("Bracket mode", bracket_mode),
+        ("Bracket shot number", bracket_shot),
+        ("RAW+JPEG quality", raw_jpeg_quality),
+        ("Live view shooting", live_view),
+    ])
+}
+
+static CANON_SHUTTER_COUNT_MODELS: &[&str] = &[
+    "Canon EOS-1D",
+    "Canon EOS-1DS",
+    "Canon EOS-1D Mark II",
+    "Canon EOS-1D Mark II N",
+    "Canon EOS-1Ds Mark II",
+];
+
+/// Item 1 of the FileInfo array, whose meaning depends on the camera model: the
+/// file number of the 20D, 30D and their Rebel and Kiss siblings, or the shutter
+/// count of the EOS-1D bodies up to the Mark II N. Its two words hold a 32-bit
+/// value, whose layout also depends on the model. Returns the name and the value
+/// of the item, or None for other models.
+pub fn canon_file_info_count(v: &[u16], model: &str) -> Option<(&'static str, String)> {
+    let (first, second) = match (v.get(1), v.get(2)) {
+        (Some(&first), Some(&second)) => (first as u32, second as u32),
+        _ => return None,
+    };
+    // file numbers are written by little-endian cameras, as a single 32-bit value
+    let value = first | (second << 16);
+    let file_number = |number: u32| format!("{}-{:04}", number / 10000, number % 10000);
+
+    if canon_model_is(model, &["20D", "350D", "REBEL XT", "Kiss Digital N"]) {
+        let number =
+            ((value & 0xffc0) >> 6) * 10000 + ((value >> 16) & 0xff) + ((value & 0x3f) << 8);
+        Some(("File number", file_number(number)))
+    } else if canon_model_is(model, &["30D", "400D", "REBEL XTi", "Kiss Digital X"]) {
+        let number =
+            ((value & 0xffc00) >> 10) * 10000 + ((value >> 16) & 0xff) + ((value & 0x3ff) << 8);
+        Some(("File number", file_number(number)))
+    } else if CANON_SHUTTER_COUNT_MODELS.contains(&model.trim_end()) {
+        Some(("Shutter count", ((first << 16) | second).to_string()))
+    } else {
+        None
+    }
+}
+
+/// Returns true if one of the names appears as whole words in the model
+fn canon_model_is(model: &str, names: &[&str]) -> bool {
+    names.iter().any(|name| {
+        model.match_indices(name).any(|(i, _)| {
+            let before = model[..i].chars().next_back();
+            let after = model[i + name.len()..].chars().next();
+            !matches!(before, Some(c) if c.is_alphanumeric())
+                && !matches!(after, Some(c) if c.is_alphanumeric())
+        })
+    })
+}
+
+/// Number of the image file, as the folder number and the file number within it
+pub fn canon_file_number(e: &TagValue) -> String {
+    match e.to_i64(0) {
+        Some(n) => format!("{}-{:04}", n / 10000, n % 10000),
+        None => panic!("{}", INV),
+    }
+}
+
+pub fn canon_serial_number(e: &TagValue) -> String {
+    match e.to_i64(0) {
+        Some(n) => format!("{:010}", n),
+        None => panic!("{}", INV),
+    }
+}
+
+/// Identifier of the camera model, e.g. 0x80000285 for the EOS 5D Mark III
+pub fn canon_model_id(e: &TagValue) -> String {
+    match e.to_i64(0) {
+        Some(n) => format!("{:#010x}", n),
+        None => panic!("{}", INV),
+    }
+}
+
+/// Lists the AF points (numbered from 1) whose bit is set in a bit mask of
+/// 16-bit words, least significant bit first
+fn canon_af_points(bits: &[u16], count: usize) -> String {
+    let points: Vec<String> = (0..count)
+        .filter(|&i| matches!(bits.get(i / 16), Some(&w) if w & (1 << (i % 16)) != 0))
+        .map(|i| (i + 1).to_string())
+        .collect();
+    if points.is_empty() {
+        "none".to_string()
+    } else {
+        points.join(" ")
+    }
+}
+
+/// AFInfo array of older cameras: count of AF points, image and AF area
+/// sizes, positions of the points, and the points in focus
+pub fn canon_af_info(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let count = v[0] as usize;
+            let mut s = format!("{} of {} AF points valid", v[1], count);
+            if let Some(bits) = v.get(8 + 2 * count..).filter(|b| !b.is_empty()) {
+                s += &format!(", in focus: {}", canon_af_points(bits, count));
+            }
+            s
+        }
+        _ => panic!("{}", INV),
+    }
+}
+
+/// AFInfo2 array of newer cameras: AF area mode, count of AF points, image
+/// sizes, sizes and positions of the points, and the points in focus
+pub fn canon_af_info2(e: &TagValue) -> String {
+    match *e {
+        TagValue::U16(ref v) => {
+            let area_mode = canon_name(
+                v[1] as i16,
+                &[
+                    (0, "Off (manual focus)"),
+                    (1, "AF point expansion (surround)"),
+                    (2, "Single-point AF"),
+                    (4, "Multi-point AF or AI AF"),
+                    (5, "Face detect AF"),
+                    (6, "Face + tracking"),
+                    (7, "Zone AF"),
+                    (8, "AF point expansion (4 point)"),
+                    (9, "Spot AF"),
+                    (10, "AF point expansion (8 point)"),
+                    (11, "Flexizone multi (49 point)"),
+                    (12, "Flexizone multi (9 point)"),
+                    (13, "Flexizone single"),
+                    (14, "Large zone AF"),
+                ],
+            );
+            let count = v[2] as usize;
+            let mut s = format!(
+                "AF area mode: {}, {} of {} AF points valid",
+                area_mode, v[3], count
+            );
+            if let Some(bits) = v.get(8 + 4 * count..).filter(|b| !b.is_empty()) {
+                s += &format!(", in focus: {}", canon_af_points(bits, count));
+            }
+            s
+        }
+        _ => panic!("{}", INV),
+    }
+}
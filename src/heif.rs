@@ -0,0 +1,263 @@
+use super::bmff::*;
+use super::image::FileType;
+use super::lowlevel::*;
+use super::types::*;
+
+/// Brands of HEIF images coded with HEVC, found in the ftyp box
+static HEIC_BRANDS: [&[u8]; 6] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx"];
+
+/// Brands of AVIF images, found in the ftyp box
+static AVIF_BRANDS: [&[u8]; 2] = [b"avif", b"avis"];
+
+fn heif_error(msg: &str) -> ExifError {
+    ExifError::HeifWithoutExif(msg.to_string())
+}
+
+/// Reads a big-endian integer of 0, 2, 4 or 8 bytes, as found in iloc
+fn read_sized(data: &[u8], offset: &mut usize, size: usize) -> Result<u64, ExifError> {
+    let raw = data
+        .get(*offset..*offset + size)
+        .ok_or_else(|| heif_error("Truncated item location"))?;
+    *offset += size;
+    Ok(match size {
+        0 => 0,
+        2 => read_u16(false, raw) as u64,
+        4 => read_u32(false, raw) as u64,
+        8 => ((read_u32(false, &raw[0..4]) as u64) << 32) + read_u32(false, &raw[4..8]) as u64,
+        _ => return Err(heif_error("Invalid field size in item location")),
+    })
+}
+
+/// Tells whether a buffer contains a HEIF (HEVC-coded) or AVIF image, looking at
+/// the brands of the ftyp box
+pub(crate) fn detect_heif(contents: &[u8]) -> Option<FileType> {
+    if contents.len() < 12 || &contents[4..8] != b"ftyp" {
+        return None;
+    }
+    let size = read_u32(false, &contents[0..4]) as usize;
+    // the brands may run past the buffer, when only the start of the file is read
+    let ftyp = contents.get(8..size.min(contents.len()))?;
+
+    // major brand, then compatible brands after the minor version
+    let brands = ftyp[0..4.min(ftyp.len())]
+        .chunks(4)
+        .chain(ftyp.get(8..).unwrap_or(&[]).chunks(4));
+    for brand in brands {
+        if HEIC_BRANDS.contains(&brand) {
+            return Some(FileType::HEIC);
+        }
+        if AVIF_BRANDS.contains(&brand) {
+            return Some(FileType::AVIF);
+        }
+    }
+    None
+}
+
+/// Parses the item info box, returning the IDs of the items of type 'Exif'
+fn exif_items(iinf: &[u8]) -> Result<Vec<u32>, ExifError> {
+    let version = *iinf
+        .first()
+        .ok_or_else(|| heif_error("Truncated item info"))?;
+    // version, flags and entry count
+    let header = if version == 0 { 6 } else { 8 };
+    let entries = iinf
+        .get(header..)
+        .ok_or_else(|| heif_error("Truncated item info"))?;
+
+    let mut items: Vec<u32> = Vec::new();
+    for infe in bmff_boxes(entries) {
+        let infe = infe.map_err(heif_error)?;
+        let data = infe.data;
+        // versions 0 and 1 of infe do not have item types
+        if infe.kind != b"infe" || data.is_empty() || data[0] < 2 {
+            continue;
+        }
+        let (id, item_type) = if data[0] == 2 {
+            (
+                data.get(4..6).map(|id| read_u16(false, id) as u32),
+                data.get(8..12),
+            )
+        } else {
+            (
+                data.get(4..8).map(|id| read_u32(false, id)),
+                data.get(10..14),
+            )
+        };
+        if let (Some(id), Some(b"Exif")) = (id, item_type) {
+            items.push(id);
+        }
+    }
+    Ok(items)
+}
+
+/// Parses the item reference box, returning the items described by each item (cdsc)
+fn content_descriptions(iref: &[u8]) -> Result<Vec<(u32, Vec<u32>)>, ExifError> {
+    let version = *iref
+        .first()
+        .ok_or_else(|| heif_error("Truncated item reference"))?;
+    let id_size = if version == 0 { 2 } else { 4 };
+    let read_id = |data: &[u8], offset: usize| -> Option<u32> {
+        let raw = data.get(offset..offset + id_size)?;
+        Some(if id_size == 2 {
+            read_u16(false, raw) as u32
+        } else {
+            read_u32(false, raw)
+        })
+    };
+
+    let mut refs: Vec<(u32, Vec<u32>)> = Vec::new();
+    for reference in bmff_boxes(iref.get(4..).unwrap_or(&[])) {
+        let reference = reference.map_err(heif_error)?;
+        if reference.kind != b"cdsc" {
+            continue;
+        }
+        let data = reference.data;
+        let from = read_id(data, 0);
+        let count = data.get(id_size..id_size + 2).map(|c| read_u16(false, c));
+        if let (Some(from), Some(count)) = (from, count) {
+            let to = (0..count as usize)
+                .filter_map(|i| read_id(data, id_size + 2 + i * id_size))
+                .collect();
+            refs.push((from, to));
+        }
+    }
+    Ok(refs)
+}
+
+/// Parses the item location box, and gathers the data of an item. `meta` is the
+/// contents of the meta box, that may contain the idat box.
+fn item_data(
+    contents: &[u8],
+    meta: &[u8],
+    iloc: &[u8],
+    item_id: u32,
+) -> Result<Vec<u8>, ExifError> {
+    let truncated = || heif_error("Truncated item location");
+    let version = *iloc.first().ok_or_else(truncated)?;
+    let sizes = iloc.get(4..6).ok_or_else(truncated)?;
+    let offset_size = (sizes[0] >> 4) as usize;
+    let length_size = (sizes[0] & 0xf) as usize;
+    let base_offset_size = (sizes[1] >> 4) as usize;
+    let index_size = if version == 0 {
+        0
+    } else {
+        (sizes[1] & 0xf) as usize
+    };
+
+    let mut offset = 6;
+    let id_size = if version < 2 { 2 } else { 4 };
+    let item_count = read_sized(iloc, &mut offset, id_size)?;
+
+    for _ in 0..item_count {
+        let id = read_sized(iloc, &mut offset, id_size)? as u32;
+        let construction_method = if version == 0 {
+            0
+        } else {
+            read_sized(iloc, &mut offset, 2)? & 0xf
+        };
+        // data reference index
+        read_sized(iloc, &mut offset, 2)?;
+        let base_offset = read_sized(iloc, &mut offset, base_offset_size)?;
+        let extent_count = read_sized(iloc, &mut offset, 2)?;
+
+        let mut data: Vec<u8> = Vec::new();
+        for _ in 0..extent_count {
+            read_sized(iloc, &mut offset, index_size)?;
+            let extent_offset = read_sized(iloc, &mut offset, offset_size)?;
+            let extent_length = read_sized(iloc, &mut offset, length_size)?;
+            if id != item_id {
+                continue;
+            }
+
+            let source = match construction_method {
+                0 => contents,
+                1 => {
+                    find_box(meta.get(4..).unwrap_or(&[]), b"idat")
+                        .map_err(heif_error)?
+                        .ok_or_else(|| heif_error("Item data box not found"))?
+                        .data
+                }
+                _ => return Err(heif_error("Unsupported item construction method")),
+            };
+            let start = base_offset
+                .checked_add(extent_offset)
+                .ok_or_else(|| heif_error("Item extent out of range"))?
+                as usize;
+            let end = if extent_length == 0 {
+                // extent goes up to the end of the source
+                source.len()
+            } else {
+                start.saturating_add(extent_length as usize)
+            };
+            data.extend_from_slice(
+                source
+                    .get(start..end)
+                    .ok_or_else(|| heif_error("Item data past EOF"))?,
+            );
+        }
+
+        if id == item_id {
+            return Ok(data);
+        }
+    }
+
+    Err(heif_error("EXIF item location not found"))
+}
+
+/// Find the embedded TIFF in a HEIF or AVIF image (that in turn contains the EXIF data).
+///
+/// EXIF data is stored as an item of type 'Exif', that describes the primary image
+/// (by means of a 'cdsc' reference). The item starts with the offset of the TIFF
+/// header, that is skipped along with whatever precedes the TIFF header.
+pub fn find_embedded_tiff_in_heif(contents: &[u8]) -> Result<Vec<u8>, ExifError> {
+    let meta = find_box(contents, b"meta")
+        .map_err(heif_error)?
+        .ok_or_else(|| heif_error("No meta box found"))?;
+    // version and flags
+    let boxes = meta
+        .data
+        .get(4..)
+        .ok_or_else(|| heif_error("Truncated meta box"))?;
+
+    let iinf = find_box(boxes, b"iinf")
+        .map_err(heif_error)?
+        .ok_or_else(|| heif_error("No item info found"))?;
+    let items = exif_items(iinf.data)?;
+
+    // the EXIF item that describes the primary image is preferred
+    let primary = match find_box(boxes, b"pitm").map_err(heif_error)? {
+        Some(pitm) if pitm.data.len() >= 6 => {
+            if pitm.data[0] == 0 {
+                Some(read_u16(false, &pitm.data[4..6]) as u32)
+            } else {
+                pitm.data.get(4..8).map(|id| read_u32(false, id))
+            }
+        }
+        _ => None,
+    };
+    let refs = match find_box(boxes, b"iref").map_err(heif_error)? {
+        Some(iref) => content_descriptions(iref.data)?,
+        None => Vec::new(),
+    };
+    let describes_primary = |item: &u32| match primary {
+        Some(primary) => refs
+            .iter()
+            .any(|(from, to)| from == item && to.contains(&primary)),
+        None => false,
+    };
+    let item = items
+        .iter()
+        .find(|item| describes_primary(item))
+        .or_else(|| items.first())
+        .ok_or_else(|| heif_error("No EXIF item found"))?;
+
+    let iloc = find_box(boxes, b"iloc")
+        .map_err(heif_error)?
+        .ok_or_else(|| heif_error("No item location found"))?;
+    let data = item_data(contents, meta.data, iloc.data, *item)?;
+
+    match skip_tiff_header_offset(&data) {
+        Some(tiff) => Ok(tiff.to_vec()),
+        None => Err(heif_error("EXIF item truncated")),
+    }
+}
@@ -0,0 +1,115 @@
+use super::lowlevel::*;
+use super::types::*;
+
+/// TIFF tag that contains the ICC profile (InterColorProfile), in IFD0
+const ICC_TAG: u16 = 34675;
+
+/// Length of the profile header, followed by the tag table
+const HEADER_LENGTH: usize = 128;
+
+/// Finds the ICC profile of a TIFF image, stored in IFD0
+pub(crate) fn find_icc_in_tiff(entries: &[ExifEntry]) -> Option<&[u8]> {
+    entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == IfdKind::Ifd0 && e.ifd.tag == ICC_TAG)
+        .map(|e| &e.ifd.data[..])
+}
+
+/// Turns a signature, e.g. b"RGB ", into text without the padding spaces
+fn signature(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw).trim_end().to_string()
+}
+
+/// Reads a signed 15.16 fixed-point number
+fn s15_fixed16(raw: &[u8]) -> f64 {
+    read_i32(false, raw) as f64 / 65536.0
+}
+
+/// Finds the data of a tag in the tag table that follows the header
+fn find_tag<'a>(profile: &'a [u8], sig: &[u8]) -> Option<&'a [u8]> {
+    let count = read_u32(false, profile.get(HEADER_LENGTH..HEADER_LENGTH + 4)?) as usize;
+    let table = profile.get(HEADER_LENGTH + 4..)?;
+    table
+        .chunks_exact(12)
+        .take(count)
+        .find(|entry| &entry[0..4] == sig)
+        .and_then(|entry| {
+            let offset = read_u32(false, &entry[4..8]) as usize;
+            let size = read_u32(false, &entry[8..12]) as usize;
+            profile.get(offset..offset.checked_add(size)?)
+        })
+}
+
+/// Decodes the text of a tag, whose type may be 'desc' (textDescriptionType
+/// of ICC v2), 'text' (textType) or 'mluc' (multiLocalizedUnicodeType of ICC
+/// v4). Of localized strings, the English one is preferred.
+fn decode_text(data: &[u8]) -> Option<String> {
+    let text = match data.get(0..4)? {
+        b"desc" => {
+            let count = read_u32(false, data.get(8..12)?) as usize;
+            data.get(12..12usize.checked_add(count)?)?
+        }
+        b"text" => data.get(8..)?,
+        b"mluc" => {
+            let count = read_u32(false, data.get(8..12)?) as usize;
+            let record_size = read_u32(false, data.get(12..16)?) as usize;
+            if record_size < 12 {
+                return None;
+            }
+            let records = data.get(16..16usize.checked_add(count.checked_mul(record_size)?)?)?;
+            let record = records
+                .chunks_exact(record_size)
+                .find(|r| &r[0..2] == b"en")
+                .or_else(|| records.chunks_exact(record_size).next())?;
+            let length = read_u32(false, &record[4..8]) as usize;
+            let offset = read_u32(false, &record[8..12]) as usize;
+            let units: Vec<u16> = data
+                .get(offset..offset.checked_add(length)?)?
+                .chunks_exact(2)
+                .map(|u| read_u16(false, u))
+                .collect();
+            let text = String::from_utf16_lossy(&units);
+            return Some(text.trim_end_matches('\0').to_string());
+        }
+        _ => return None,
+    };
+    Some(
+        String::from_utf8_lossy(text)
+            .trim_end_matches('\0')
+            .to_string(),
+    )
+}
+
+/// Decodes an 'XYZ ' tag, keeping its first value
+fn decode_xyz(data: &[u8]) -> Option<[f64; 3]> {
+    if data.get(0..4)? != b"XYZ " {
+        return None;
+    }
+    let xyz = data.get(8..20)?;
+    Some([
+        s15_fixed16(&xyz[0..4]),
+        s15_fixed16(&xyz[4..8]),
+        s15_fixed16(&xyz[8..12]),
+    ])
+}
+
+/// Decodes the header of an ICC profile, and the description, copyright and
+/// white point tags
+pub(crate) fn parse_icc(profile: Vec<u8>) -> Result<IccProfile, String> {
+    if profile.len() < HEADER_LENGTH || &profile[36..40] != b"acsp" {
+        return Err("ICC profile header unrecognized".to_string());
+    }
+
+    Ok(IccProfile {
+        major_version: profile[8],
+        minor_version: profile[9] >> 4,
+        class: signature(&profile[12..16]),
+        color_space: signature(&profile[16..20]),
+        connection_space: signature(&profile[20..24]),
+        rendering_intent: read_u32(false, &profile[64..68]),
+        description: find_tag(&profile, b"desc").and_then(decode_text),
+        copyright: find_tag(&profile, b"cprt").and_then(decode_text),
+        white_point: find_tag(&profile, b"wtpt").and_then(decode_xyz),
+        data: profile,
+    })
+}
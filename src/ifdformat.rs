@@ -27,7 +27,7 @@ pub fn numarray_to_string<T: Display>(numbers: &[T]) -> String {
 /// Convert a IfdEntry into a tuple of TagValue
 pub fn tag_value_new(f: &IfdEntry) -> TagValue {
     match f.format {
-        IfdFormat::Ascii => {
+        IfdFormat::Ascii | IfdFormat::Utf8 => {
             // Remove \0, there may be more than one
             let mut tot = f.data.len();
             while tot > 0 && f.data[tot - 1] == 0 {
@@ -36,7 +36,11 @@ pub fn tag_value_new(f: &IfdEntry) -> TagValue {
             // In theory it should be pure ASCII but we admit UTF-8
             let s = String::from_utf8_lossy(&f.data[0..tot]);
             let s = s.into_owned();
-            TagValue::Ascii(s)
+            if f.format == IfdFormat::Utf8 {
+                TagValue::Utf8(s)
+            } else {
+                TagValue::Ascii(s)
+            }
         }
         IfdFormat::U16 => {
             if f.data.len() < (f.count as usize * 2) {
@@ -66,7 +70,7 @@ pub fn tag_value_new(f: &IfdEntry) -> TagValue {
             let a = read_i8_array(f.count, &f.data[..]);
             TagValue::I8(a)
         }
-        IfdFormat::U32 => {
+        IfdFormat::U32 | IfdFormat::Ifd => {
             if f.data.len() < (f.count as usize * 4) {
                 return TagValue::Invalid(f.data.clone(), f.le, f.format as u16, f.count);
             }
@@ -94,6 +98,20 @@ pub fn tag_value_new(f: &IfdEntry) -> TagValue {
             let a = read_f64_array(f.count, &f.data[..]);
             TagValue::F64(a)
         }
+        IfdFormat::U64 | IfdFormat::Ifd64 => {
+            if f.data.len() < (f.count as usize * 8) {
+                return TagValue::Invalid(f.data.clone(), f.le, f.format as u16, f.count);
+            }
+            let a = read_u64_array(f.le, f.count, &f.data[..]);
+            TagValue::U64(a)
+        }
+        IfdFormat::I64 => {
+            if f.data.len() < (f.count as usize * 8) {
+                return TagValue::Invalid(f.data.clone(), f.le, f.format as u16, f.count);
+            }
+            let a = read_i64_array(f.le, f.count, &f.data[..]);
+            TagValue::I64(a)
+        }
         IfdFormat::URational => {
             if f.data.len() < (f.count as usize * 8) {
                 return TagValue::Invalid(f.data.clone(), f.le, f.format as u16, f.count);
@@ -1,50 +1,126 @@
+use crate::heif::detect_heif;
+use crate::jxl::is_jxl;
+use crate::lowlevel::read_u32;
+use crate::photoshop::is_psd;
+use crate::png::PNG_SIGNATURE;
 use crate::types::ExifError;
+use crate::webp::is_webp;
+use std::io;
+use std::io::Read;
 
 pub enum FileType {
     Unknown,
     JPEG,
     TIFF,
+    PNG,
+    WEBP,
+    HEIC,
+    AVIF,
+    JXL,
+    PSD,
 }
 
-/// Detect the type of an image contained in a byte buffer
-pub fn detect_type(contents: &[u8]) -> FileType {
-    if contents.len() < 11 {
-        return FileType::Unknown;
+/// Returns true if the buffer starts with SOI, followed by a marker that may
+/// open a JPEG image: APPn (JFIF, Exif, ICC, Adobe, Photoshop...), COM, or
+/// any table or frame marker. Restart markers, SOI and EOI are not valid there.
+fn is_jpeg(contents: &[u8]) -> bool {
+    if contents.len() < 4 || contents[0..3] != [0xff, 0xd8, 0xff] {
+        return false;
+    }
+    match contents[3] {
+        0xd0..=0xd9 => false,
+        marker => marker >= 0xc0 && marker != 0xff,
     }
+}
 
-    if contents[0] == 0xff && contents[1] == 0xd8 &&
-			contents[2] == 0xff && // contents[3] == 0xe0 &&
-			contents[6] == b'J' && contents[7] == b'F' &&
-			contents[8] == b'I' && contents[9] == b'F' &&
-			contents[10] == 0
-    {
+/// Detect the type of an image contained in a byte buffer
+pub fn detect_type(contents: &[u8]) -> FileType {
+    if is_jpeg(contents) {
         return FileType::JPEG;
     }
-    if contents[0] == 0xff && contents[1] == 0xd8 &&
-			contents[2] == 0xff && // contents[3] == 0xe0 &&
-			contents[6] == b'E' && contents[7] == b'x' &&
-			contents[8] == b'i' && contents[9] == b'f' &&
-			contents[10] == 0
-    {
-        return FileType::JPEG;
+    if contents.len() < 4 {
+        return FileType::Unknown;
     }
-    if contents[0] == b'I' && contents[1] == b'I' && contents[2] == 42 && contents[3] == 0 {
+    // 42 for TIFF, 43 for BigTIFF
+    if contents[0] == b'I'
+        && contents[1] == b'I'
+        && (contents[2] == 42 || contents[2] == 43)
+        && contents[3] == 0
+    {
         /* TIFF little-endian */
         return FileType::TIFF;
     }
-    if contents[0] == b'M' && contents[1] == b'M' && contents[2] == 0 && contents[3] == 42 {
+    if contents[0] == b'M'
+        && contents[1] == b'M'
+        && contents[2] == 0
+        && (contents[3] == 42 || contents[3] == 43)
+    {
         /* TIFF big-endian */
         return FileType::TIFF;
     }
+    if contents.starts_with(&PNG_SIGNATURE) {
+        return FileType::PNG;
+    }
+    if is_webp(contents) {
+        return FileType::WEBP;
+    }
+    if let Some(heif) = detect_heif(contents) {
+        return heif;
+    }
+    if is_jxl(contents) {
+        return FileType::JXL;
+    }
+    if is_psd(contents) {
+        return FileType::PSD;
+    }
 
     FileType::Unknown
 }
 
-/// Find the embedded TIFF in a JPEG image (that in turn contains the EXIF data)
-pub fn find_embedded_tiff_in_jpeg(contents: &[u8]) -> Result<(usize, usize), ExifError> {
-    let mut offset = 2 as usize;
+/// Marker segment of a JPEG image
+pub(crate) struct JpegSegment<'a> {
+    /// Marker code, e.g. 0xffe1 for APP1
+    pub marker: u16,
+    /// Offset of the marker within the image
+    pub offset: usize,
+    /// Contents of the segment, excluding marker and size word
+    pub data: &'a [u8],
+}
+
+impl<'a> JpegSegment<'a> {
+    /// Offset of the first byte after the segment
+    pub fn end(&self) -> usize {
+        self.offset + 4 + self.data.len()
+    }
+
+    /// Returns true if this is the APP1 segment that contains EXIF data
+    pub fn is_exif(&self) -> bool {
+        self.marker == 0xffe1 && self.data.starts_with(b"Exif\0\0")
+    }
+}
+
+/// Iterator over the marker segments of a JPEG image, from the one that
+/// follows SOI up to SOS (start of scan), included. Entropy-coded data that
+/// follows SOS is not visited.
+pub(crate) struct JpegSegments<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+pub(crate) fn jpeg_segments(contents: &[u8]) -> JpegSegments<'_> {
+    JpegSegments {
+        contents,
+        offset: 2,
+        done: false,
+    }
+}
+
+impl<'a> JpegSegments<'a> {
+    fn read_segment(&self) -> Result<JpegSegment<'a>, ExifError> {
+        let contents = self.contents;
+        let offset = self.offset;
 
-    while offset < contents.len() {
         if contents.len() < (offset + 4) {
             return Err(ExifError::JpegWithoutExif(
                 "JPEG truncated in marker header".to_string(),
@@ -60,46 +136,319 @@ pub fn find_embedded_tiff_in_jpeg(contents: &[u8]) -> Result<(usize, usize), Exi
             )));
         }
 
-        offset += 2;
-        let size = (contents[offset] as usize) * 256 + (contents[offset + 1] as usize);
+        let size = (contents[offset + 2] as usize) * 256 + (contents[offset + 3] as usize);
 
         if size < 2 {
             return Err(ExifError::JpegWithoutExif(
                 "JPEG marker size must be at least 2 (because of the size word)".to_string(),
             ));
         }
-        if contents.len() < (offset + size) {
+        if contents.len() < (offset + 2 + size) {
             return Err(ExifError::JpegWithoutExif(
                 "JPEG truncated in marker body".to_string(),
             ));
         }
 
-        if marker == 0xffe1 {
-            if size < 8 {
-                return Err(ExifError::JpegWithoutExif(
-                    "EXIF preamble truncated".to_string(),
-                ));
-            }
+        Ok(JpegSegment {
+            marker,
+            offset,
+            data: &contents[offset + 4..offset + 2 + size],
+        })
+    }
+}
+
+impl<'a> Iterator for JpegSegments<'a> {
+    type Item = Result<JpegSegment<'a>, ExifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.contents.len() {
+            return None;
+        }
 
-            if contents[offset + 2..offset + 8] != [b'E', b'x', b'i', b'f', 0, 0] {
-                return Err(ExifError::JpegWithoutExif(
-                    "EXIF preamble unrecognized".to_string(),
-                ));
+        let segment = self.read_segment();
+        match segment {
+            Ok(ref segment) => {
+                self.offset = segment.end();
+                // last marker before image data
+                self.done = segment.marker == 0xffda;
             }
+            Err(_) => self.done = true,
+        }
+        Some(segment)
+    }
+}
+
+/// Reads the start of a JPEG image, from SOI up to SOS (start of scan), included,
+/// so that it can be walked by `jpeg_segments()`. Entropy-coded data is not read.
+///
+/// Reading stops early at EOF or at an invalid marker, and the data read so far
+/// is returned. `jpeg_segments()` reports the problem later on.
+pub(crate) fn read_jpeg_header<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut contents: Vec<u8> = Vec::new();
+    if reader.by_ref().take(2).read_to_end(&mut contents)? < 2 {
+        return Ok(contents);
+    }
+
+    loop {
+        let offset = contents.len();
+        if reader.by_ref().take(4).read_to_end(&mut contents)? < 4 {
+            break;
+        }
+
+        let marker: u16 = u16::from(contents[offset]) * 256 + u16::from(contents[offset + 1]);
+        let size = (contents[offset + 2] as u64) * 256 + (contents[offset + 3] as u64);
+        if marker < 0xff00 || size < 2 {
+            break;
+        }
 
-            // The offset and size of the block, excluding size and 'Exif\0\0'.
-            return Ok((offset + 8, size - 8));
+        if reader.by_ref().take(size - 2).read_to_end(&mut contents)? < (size - 2) as usize {
+            break;
         }
         if marker == 0xffda {
+            // last marker before image data
+            break;
+        }
+    }
+
+    Ok(contents)
+}
+
+/// Removes the "Exif\0\0" preamble that some writers put before the TIFF block,
+/// as it would be found in the APP1 segment of a JPEG image
+pub(crate) fn strip_exif_preamble(data: &[u8]) -> &[u8] {
+    if data.starts_with(b"Exif\0\0") {
+        &data[6..]
+    } else {
+        data
+    }
+}
+
+/// Find the embedded TIFF in a JPEG image (that in turn contains the EXIF data).
+///
+/// Every APP1 segment is visited, the first one with the "Exif\0\0" preamble
+/// is taken. APP1 segments of other kinds, like XMP, may come before it.
+pub fn find_embedded_tiff_in_jpeg(contents: &[u8]) -> Result<(usize, usize), ExifError> {
+    for segment in jpeg_segments(contents) {
+        let segment = segment?;
+
+        // other APP1 segments, like XMP, are skipped
+        if segment.is_exif() {
+            // The offset and size of the block, excluding marker, size and 'Exif\0\0'.
+            return Ok((segment.offset + 10, segment.data.len() - 6));
+        }
+        if segment.marker == 0xffda {
             // last marker
             return Err(ExifError::JpegWithoutExif(
                 "Last mark found and no EXIF".to_string(),
             ));
         }
-        offset += size;
     }
 
     Err(ExifError::JpegWithoutExif(
         "Scan past EOF and no EXIF found".to_string(),
     ))
 }
+
+/// Namespace that opens the APP1 segment of a JPEG image that contains XMP
+pub(crate) static XMP_JPEG_NS: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// Find the XMP packet of a JPEG image, stored in an APP1 segment
+pub(crate) fn find_xmp_in_jpeg(contents: &[u8]) -> Option<&[u8]> {
+    jpeg_segments(contents)
+        .map_while(Result::ok)
+        .find(|s| s.marker == 0xffe1 && s.data.starts_with(XMP_JPEG_NS))
+        .map(|s| &s.data[XMP_JPEG_NS.len()..])
+}
+
+/// Identifier that opens the APP2 segments of a JPEG image that contain the
+/// ICC profile
+static ICC_JPEG_ID: &[u8] = b"ICC_PROFILE\0";
+
+/// Find the ICC profile of a JPEG image. A profile is split in chunks, each
+/// stored in an APP2 segment with its sequence number (starting at 1) and the
+/// number of chunks.
+pub(crate) fn find_icc_in_jpeg(contents: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let mut chunks: Vec<(u8, &[u8])> = Vec::new();
+    let mut count = 0;
+    for segment in jpeg_segments(contents).map_while(Result::ok) {
+        if segment.marker != 0xffe2 || !segment.data.starts_with(ICC_JPEG_ID) {
+            continue;
+        }
+        let data = &segment.data[ICC_JPEG_ID.len()..];
+        if data.len() < 2 {
+            return Err("ICC profile chunk truncated".to_string());
+        }
+        count = data[1];
+        chunks.push((data[0], &data[2..]));
+    }
+    if chunks.is_empty() {
+        return Ok(None);
+    }
+
+    chunks.sort_by_key(|&(sequence, _)| sequence);
+    let complete = chunks.len() == count as usize
+        && chunks
+            .iter()
+            .enumerate()
+            .all(|(i, &(sequence, _))| sequence as usize == i + 1);
+    if !complete {
+        return Err(format!(
+            "ICC profile has {} chunks, expected {}",
+            chunks.len(),
+            count
+        ));
+    }
+    let chunks: Vec<&[u8]> = chunks.iter().map(|&(_, chunk)| chunk).collect();
+    Ok(Some(chunks.concat()))
+}
+
+/// Identifier that opens the APP13 segment of a JPEG image that contains the
+/// Photoshop image resource block
+pub(crate) static PHOTOSHOP_JPEG_ID: &[u8] = b"Photoshop 3.0\0";
+
+/// Find the Photoshop image resource block of a JPEG image. A large block is
+/// split across several APP13 segments, that are joined.
+pub(crate) fn find_photoshop_in_jpeg(contents: &[u8]) -> Option<Vec<u8>> {
+    let mut irb: Option<Vec<u8>> = None;
+    for segment in jpeg_segments(contents).map_while(Result::ok) {
+        if segment.marker == 0xffed && segment.data.starts_with(PHOTOSHOP_JPEG_ID) {
+            irb.get_or_insert_with(Vec::new)
+                .extend_from_slice(&segment.data[PHOTOSHOP_JPEG_ID.len()..]);
+        }
+    }
+    irb
+}
+
+/// Namespace that opens the APP1 segments of a JPEG image that contain the
+/// extended XMP packet, when the packet is too large for a single segment
+pub(crate) static XMP_EXTENSION_NS: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+
+/// Reassembles the extended XMP packet of a JPEG image, whose GUID is given by
+/// the xmpNote:HasExtendedXMP property of the main packet.
+///
+/// Each APP1 segment holds a chunk of the packet: the GUID (the MD5 digest of
+/// the whole packet, as 32 hex digits), the length of the whole packet and the
+/// offset of the chunk, followed by the chunk data. Chunks of other GUIDs are
+/// skipped, they belong to packets that are not referred to anymore.
+pub(crate) fn find_extended_xmp_in_jpeg(contents: &[u8], guid: &str) -> Result<Vec<u8>, String> {
+    let mut full_length: Option<usize> = None;
+    let mut chunks: Vec<(usize, &[u8])> = Vec::new();
+
+    for segment in jpeg_segments(contents).map_while(Result::ok) {
+        if segment.marker != 0xffe1 || !segment.data.starts_with(XMP_EXTENSION_NS) {
+            continue;
+        }
+        let data = &segment.data[XMP_EXTENSION_NS.len()..];
+        if data.len() < 40 || !data[0..32].eq_ignore_ascii_case(guid.as_bytes()) {
+            continue;
+        }
+
+        let length = read_u32(false, &data[32..36]) as usize;
+        if *full_length.get_or_insert(length) != length {
+            return Err("Extended XMP chunks disagree on the packet length".to_string());
+        }
+        chunks.push((read_u32(false, &data[36..40]) as usize, &data[40..]));
+    }
+
+    let full_length = full_length.ok_or_else(|| format!("Extended XMP {} not found", guid))?;
+    chunks.sort_by_key(|&(offset, _)| offset);
+
+    let mut packet: Vec<u8> = Vec::new();
+    for (offset, chunk) in chunks {
+        if offset > packet.len() {
+            return Err(format!(
+                "Extended XMP is missing bytes at offset {}",
+                packet.len()
+            ));
+        }
+        // duplicate chunks overlap the data already copied
+        let end = (offset + chunk.len()).min(full_length);
+        if end > packet.len() {
+            packet.extend_from_slice(&chunk[packet.len() - offset..end - offset]);
+        }
+    }
+    if packet.len() != full_length {
+        return Err(format!(
+            "Extended XMP has {} bytes, expected {}",
+            packet.len(),
+            full_length
+        ));
+    }
+
+    let digest = format!("{:X}", md5::compute(&packet));
+    if !digest.eq_ignore_ascii_case(guid) {
+        return Err(format!(
+            "Extended XMP digest {} does not match GUID {}",
+            digest, guid
+        ));
+    }
+    Ok(packet)
+}
+
+/// Replace the EXIF segment of a JPEG image with a new TIFF block (e.g. one made
+/// by `serialize_tiff()`). The segment is inserted if the image has no EXIF data,
+/// and removed if `tiff` is `None`.
+///
+/// Every other segment, and the image data, are copied unchanged.
+pub fn replace_exif_in_jpeg(contents: &[u8], tiff: Option<&[u8]>) -> Result<Vec<u8>, ExifError> {
+    if contents.len() < 2 || contents[0..2] != [0xff, 0xd8] {
+        // no SOI marker
+        return Err(ExifError::FileTypeUnknown);
+    }
+
+    let mut app1: Vec<u8> = Vec::new();
+    if let Some(tiff) = tiff {
+        // size word, 'Exif\0\0' and the TIFF block
+        let size = 2 + 6 + tiff.len();
+        if size > 0xffff {
+            return Err(ExifError::SerializeError(format!(
+                "EXIF block of {} bytes does not fit in a JPEG segment",
+                tiff.len()
+            )));
+        }
+        app1.extend_from_slice(&[0xff, 0xe1, (size >> 8) as u8, size as u8]);
+        app1.extend_from_slice(b"Exif\0\0");
+        app1.extend_from_slice(tiff);
+    }
+
+    let mut has_exif = false;
+    for segment in jpeg_segments(contents) {
+        if segment?.is_exif() {
+            has_exif = true;
+            break;
+        }
+    }
+
+    let mut out: Vec<u8> = Vec::with_capacity(contents.len() + app1.len());
+    out.extend_from_slice(&contents[0..2]);
+    let mut written = false;
+    let mut offset = 2;
+
+    for segment in jpeg_segments(contents) {
+        let segment = segment?;
+        offset = segment.end();
+
+        if segment.is_exif() {
+            // replaced in place, and duplicates are dropped
+            if !written {
+                out.extend_from_slice(&app1);
+                written = true;
+            }
+            continue;
+        }
+        // new EXIF segment goes right after SOI, or after JFIF APP0
+        if !has_exif && !written && segment.marker != 0xffe0 {
+            out.extend_from_slice(&app1);
+            written = true;
+        }
+        out.extend_from_slice(&contents[segment.offset..segment.end()]);
+    }
+
+    if !written {
+        out.extend_from_slice(&app1);
+    }
+    // image data, and whatever follows it
+    out.extend_from_slice(&contents[offset..]);
+
+    Ok(out)
+}
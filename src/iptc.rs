@@ -0,0 +1,141 @@
+use super::lowlevel::*;
+use super::types::*;
+
+/// TIFF tag that contains the IPTC-IIM stream (IPTC-NAA), in IFD0
+const IPTC_TAG: u16 = 33723;
+
+/// Escape sequence of the CodedCharacterSet dataset that selects UTF-8
+static UTF8_ESCAPE: &[u8] = b"\x1b%G";
+
+/// Tag marker that starts every dataset
+const TAG_MARKER: u8 = 0x1c;
+
+/// Finds the IPTC-IIM stream of a TIFF image, stored in IFD0 as bytes or as
+/// 32-bit integers
+pub(crate) fn find_iptc_in_tiff(entries: &[ExifEntry]) -> Option<&[u8]> {
+    entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == IfdKind::Ifd0 && e.ifd.tag == IPTC_TAG)
+        .map(|e| &e.ifd.data[..])
+}
+
+fn iptc_tag(record: u8, dataset: u8) -> IptcTag {
+    match (record as u16) << 8 | dataset as u16 {
+        0x015a => IptcTag::CodedCharacterSet,
+        0x0200 => IptcTag::RecordVersion,
+        0x0205 => IptcTag::ObjectName,
+        0x020a => IptcTag::Urgency,
+        0x020f => IptcTag::Category,
+        0x0214 => IptcTag::SupplementalCategories,
+        0x0219 => IptcTag::Keywords,
+        0x0228 => IptcTag::SpecialInstructions,
+        0x0237 => IptcTag::DateCreated,
+        0x023c => IptcTag::TimeCreated,
+        0x0250 => IptcTag::Byline,
+        0x0255 => IptcTag::BylineTitle,
+        0x025a => IptcTag::City,
+        0x025c => IptcTag::SubLocation,
+        0x025f => IptcTag::ProvinceState,
+        0x0264 => IptcTag::CountryCode,
+        0x0265 => IptcTag::Country,
+        0x0267 => IptcTag::OriginalTransmissionReference,
+        0x0269 => IptcTag::Headline,
+        0x026e => IptcTag::Credit,
+        0x0273 => IptcTag::Source,
+        0x0274 => IptcTag::CopyrightNotice,
+        0x0276 => IptcTag::Contact,
+        0x0278 => IptcTag::Caption,
+        0x027a => IptcTag::WriterEditor,
+        _ => IptcTag::Unknown,
+    }
+}
+
+/// Decodes text in UTF-8 if the stream says so. Otherwise the stream should be
+/// in ISO 8859-1, but many writers put UTF-8 there without saying it, so valid
+/// UTF-8 is taken as such.
+fn decode_text(data: &[u8], utf8: bool) -> String {
+    if utf8 {
+        return String::from_utf8_lossy(data).into_owned();
+    }
+    match std::str::from_utf8(data) {
+        Ok(s) => s.to_string(),
+        Err(_) => data.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Splits an IPTC-IIM stream into datasets. Each dataset starts with the tag
+/// marker, record and dataset numbers, and a 16-bit length. If the most
+/// significant bit of the length is set, the rest of it is the size of the
+/// actual length, that follows (extended dataset).
+///
+/// Parsing stops at the first byte that is not a tag marker, since streams are
+/// often padded. Datasets found before a truncated one are kept, and the
+/// problem is reported as a warning.
+pub(crate) fn parse_iptc(contents: &[u8], warnings: &mut Vec<String>) -> Option<IptcData> {
+    let mut raw: Vec<(u8, u8, &[u8])> = Vec::new();
+    let mut offset = 0;
+
+    while contents.get(offset) == Some(&TAG_MARKER) {
+        let header = match contents.get(offset + 1..offset + 5) {
+            Some(header) => header,
+            None => {
+                warnings.push("IPTC dataset truncated in header".to_string());
+                break;
+            }
+        };
+        let (record, dataset) = (header[0], header[1]);
+        let mut length = read_u16(false, &header[2..4]) as usize;
+        offset += 5;
+
+        if length & 0x8000 != 0 {
+            let size = length & 0x7fff;
+            let extended = match contents.get(offset..offset + size) {
+                Some(extended) if size <= 4 => extended,
+                _ => {
+                    warnings.push("IPTC dataset with invalid extended length".to_string());
+                    break;
+                }
+            };
+            length = extended.iter().fold(0, |l, &b| (l << 8) | b as usize);
+            offset += size;
+        }
+
+        match contents.get(offset..offset.saturating_add(length)) {
+            Some(data) => raw.push((record, dataset, data)),
+            None => {
+                warnings.push("IPTC dataset truncated in data".to_string());
+                break;
+            }
+        }
+        offset += length;
+    }
+
+    let utf8 = raw.iter().any(|&(record, dataset, data)| {
+        iptc_tag(record, dataset) == IptcTag::CodedCharacterSet && data == UTF8_ESCAPE
+    });
+
+    let datasets: Vec<IptcDataset> = raw
+        .into_iter()
+        .map(|(record, dataset, data)| {
+            let tag = iptc_tag(record, dataset);
+            let value = match tag {
+                IptcTag::RecordVersion if data.len() == 2 => read_u16(false, data).to_string(),
+                IptcTag::CodedCharacterSet if data == UTF8_ESCAPE => "UTF-8".to_string(),
+                _ => decode_text(data, utf8),
+            };
+            IptcDataset {
+                record,
+                dataset,
+                tag,
+                data: data.to_vec(),
+                value,
+            }
+        })
+        .collect();
+
+    if datasets.is_empty() {
+        None
+    } else {
+        Some(IptcData { datasets })
+    }
+}
@@ -0,0 +1,65 @@
+use super::bmff::*;
+#[cfg(feature = "brotli")]
+use super::lowlevel::*;
+use super::types::*;
+
+/// Signature box at the start of a JPEG XL image in the ISO-BMFF container
+static JXL_CONTAINER_SIGNATURE: [u8; 12] = [
+    0, 0, 0, 0x0c, b'J', b'X', b'L', b' ', 0x0d, 0x0a, 0x87, 0x0a,
+];
+
+/// Signature at the start of a naked JPEG XL codestream
+static JXL_CODESTREAM_SIGNATURE: [u8; 2] = [0xff, 0x0a];
+
+fn jxl_error(msg: &str) -> ExifError {
+    ExifError::JxlWithoutExif(msg.to_string())
+}
+
+/// Returns true if the buffer starts with a JPEG XL codestream or container
+pub(crate) fn is_jxl(contents: &[u8]) -> bool {
+    contents.starts_with(&JXL_CONTAINER_SIGNATURE)
+        || contents.starts_with(&JXL_CODESTREAM_SIGNATURE)
+}
+
+/// Decompresses the contents of a brob box, past the original box type
+#[cfg(feature = "brotli")]
+fn decompress_brob(data: &[u8]) -> Result<Vec<u8>, ExifError> {
+    inflate_brotli(data).ok_or_else(|| jxl_error("Invalid Brotli stream in brob box"))
+}
+
+#[cfg(not(feature = "brotli"))]
+fn decompress_brob(_data: &[u8]) -> Result<Vec<u8>, ExifError> {
+    Err(jxl_error(
+        "Brotli-compressed Exif box, the brotli feature is needed to read it",
+    ))
+}
+
+/// Find the embedded TIFF in a JPEG XL image (that in turn contains the EXIF data).
+///
+/// Only images in the ISO-BMFF container may carry EXIF data, in an Exif box.
+/// The box may also be Brotli-compressed, within a brob box. Those are read only
+/// if the crate is built with the `brotli` feature.
+pub fn find_embedded_tiff_in_jxl(contents: &[u8]) -> Result<Vec<u8>, ExifError> {
+    if contents.starts_with(&JXL_CODESTREAM_SIGNATURE) {
+        return Err(jxl_error("Naked codestream, without metadata"));
+    }
+
+    for item in bmff_boxes(contents) {
+        let item = item.map_err(jxl_error)?;
+
+        let exif = if item.kind == b"Exif" {
+            item.data.to_vec()
+        } else if item.kind == b"brob" && item.data.starts_with(b"Exif") {
+            decompress_brob(&item.data[4..])?
+        } else {
+            continue;
+        };
+
+        return match skip_tiff_header_offset(&exif) {
+            Some(tiff) => Ok(tiff.to_vec()),
+            None => Err(jxl_error("Exif box truncated")),
+        };
+    }
+
+    Err(jxl_error("No Exif box found"))
+}
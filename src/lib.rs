@@ -1,4 +1,15 @@
-//! RExif is a native Rust create, written to extract EXIF data from JPEG and TIFF images.
+//! RExif is a native Rust create, written to extract EXIF data from JPEG, TIFF, PNG, WebP,
+//! HEIF, AVIF and JPEG XL images, and PSD files.
+//!
+//! The XMP packet of JPEG, TIFF, PNG and WebP images is parsed as well, see `ExifData::xmp`.
+//! So are the IPTC-IIM datasets of JPEG and TIFF images (`ExifData::iptc`), the Photoshop
+//! image resources (`ExifData::photoshop`) and the ICC profile (`ExifData::icc`). The
+//! Multi-Picture Format index of JPEG images (`ExifData::mpf`) locates further images
+//! stored after the primary one, like stereo pairs, depth maps or HDR gain maps.
+//! The color and raw image tags of DNG files are gathered by `ExifData::dng()`.
+//! The MakerNote of Canon cameras is decoded into entries of `Namespace::Canon`.
+//!
+//! Brotli-compressed metadata of JPEG XL images is read only if the `brotli` feature is enabled.
 //!
 //! Note that it is in very early stages of development. Any sort of feedback is welcome!
 //!
@@ -47,6 +58,7 @@ pub use self::types::*;
 mod types_impl;
 pub use self::types_impl::*;
 mod image;
+pub use self::image::replace_exif_in_jpeg;
 use self::image::*;
 mod ifdformat;
 mod tiff;
@@ -54,8 +66,36 @@ use self::tiff::*;
 mod exif;
 mod exifpost;
 mod exifreadable;
+mod writer;
+pub use self::writer::*;
+mod strip;
+pub use self::strip::*;
+mod png;
+pub use self::png::find_embedded_tiff_in_png;
+use self::png::{find_icc_in_png, find_xmp_in_png};
+mod webp;
+pub use self::webp::find_embedded_tiff_in_webp;
+use self::webp::{find_icc_in_webp, find_xmp_in_webp};
+mod bmff;
+mod heif;
+pub use self::heif::find_embedded_tiff_in_heif;
+mod jxl;
+pub use self::jxl::find_embedded_tiff_in_jxl;
+mod xmp;
+use self::xmp::*;
+mod iptc;
+use self::iptc::*;
+mod photoshop;
+use self::photoshop::*;
+mod icc;
+use self::icc::*;
+mod mpf;
+use self::mpf::*;
+mod dng;
+mod canon;
 
-/// Parse a byte buffer that should contain a TIFF or JPEG image.
+/// Parse a byte buffer that should contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF
+/// or JPEG XL image, or a PSD file.
 /// Tries to detect format and parse EXIF data.
 ///
 /// Prints warnings to stderr.
@@ -65,43 +105,286 @@ pub fn parse_buffer(contents: &[u8]) -> ExifResult {
     res
 }
 
-/// Parse a byte buffer that should contain a TIFF or JPEG image.
+/// Parse a byte buffer that should contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF
+/// or JPEG XL image, or a PSD file.
 /// Tries to detect format and parse EXIF data.
 ///
 /// Returns warnings alongside result.
 pub fn parse_buffer_quiet(contents: &[u8]) -> (ExifResult, Vec<String>) {
-    let mime = detect_type(contents);
+    let file_type = detect_type(contents);
     let mut warnings = vec![];
-    let (res, mime) = match mime {
+    let (res, mime) = match file_type {
         FileType::Unknown => return (Err(ExifError::FileTypeUnknown), warnings),
         FileType::TIFF => (parse_tiff(contents, &mut warnings), "image/tiff"),
         FileType::JPEG => (
-            find_embedded_tiff_in_jpeg(contents).and_then(|(offset, size)| {
-                parse_tiff(&contents[offset..offset + size], &mut warnings)
-            }),
+            find_embedded_tiff_in_jpeg(contents)
+                .and_then(|(offset, size)| {
+                    parse_tiff(&contents[offset..offset + size], &mut warnings)
+                })
+                .or_else(|e| {
+                    // images exported by Photoshop may have EXIF data only in APP13
+                    match find_photoshop_in_jpeg(contents) {
+                        Some(ref irb) => match find_resource(irb, EXIF_RESOURCE) {
+                            Some(tiff) => parse_tiff(tiff, &mut warnings),
+                            None => Err(e),
+                        },
+                        None => Err(e),
+                    }
+                }),
             "image/jpeg",
         ),
+        FileType::PNG => (
+            find_embedded_tiff_in_png(contents).and_then(|tiff| parse_tiff(&tiff, &mut warnings)),
+            "image/png",
+        ),
+        FileType::WEBP => (
+            find_embedded_tiff_in_webp(contents).and_then(|tiff| parse_tiff(tiff, &mut warnings)),
+            "image/webp",
+        ),
+        FileType::HEIC => (
+            find_embedded_tiff_in_heif(contents).and_then(|tiff| parse_tiff(&tiff, &mut warnings)),
+            "image/heic",
+        ),
+        FileType::AVIF => (
+            find_embedded_tiff_in_heif(contents).and_then(|tiff| parse_tiff(&tiff, &mut warnings)),
+            "image/avif",
+        ),
+        FileType::JXL => (
+            find_embedded_tiff_in_jxl(contents).and_then(|tiff| parse_tiff(&tiff, &mut warnings)),
+            "image/jxl",
+        ),
+        FileType::PSD => (
+            find_photoshop_in_psd(contents).and_then(|irb| {
+                match find_resource(irb, EXIF_RESOURCE) {
+                    Some(tiff) => parse_tiff(tiff, &mut warnings),
+                    None => Err(ExifError::PsdWithoutExif(
+                        "No EXIF resource found".to_string(),
+                    )),
+                }
+            }),
+            "image/vnd.adobe.photoshop",
+        ),
     };
 
-    (
-        res.map(|entries| ExifData {
-            mime: mime.to_string(),
-            entries,
-        }),
-        warnings,
-    )
+    let res = read_metadata_of(&file_type, mime, contents, res, &mut warnings);
+    (res, warnings)
 }
 
-/// Try to read and parse an open file that is expected to contain an image
-pub fn read_file(f: &mut File) -> ExifResult {
-    f.seek(SeekFrom::Start(0))?;
+/// Gathers the metadata of an image whatever the outcome of EXIF parsing, since
+/// images without EXIF data may still carry XMP, IPTC-IIM, an ICC profile, etc.
+/// The EXIF error is returned if no other metadata is found, and becomes a
+/// warning otherwise, along with empty `entries`.
+fn read_metadata_of(
+    file_type: &FileType,
+    mime: &str,
+    contents: &[u8],
+    res: ExifEntryResult,
+    warnings: &mut Vec<String>,
+) -> ExifResult {
+    let (entries, error) = match res {
+        Ok(entries) => (entries, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let exif = read_metadata(file_type, mime, contents, entries, warnings);
+    match error {
+        None => Ok(exif),
+        Some(e) => {
+            if exif.xmp.is_none()
+                && exif.iptc.is_none()
+                && exif.photoshop.is_none()
+                && exif.icc.is_none()
+                && exif.mpf.is_none()
+            {
+                return Err(e);
+            }
+            warnings.push(e.to_string());
+            Ok(exif)
+        }
+    }
+}
+
+/// Gathers the metadata of an image, given its EXIF entries: XMP, IPTC-IIM,
+/// Photoshop resources and the ICC profile are looked for in the image, or in
+/// the entries for TIFF images. So is the Multi-Picture Format index of JPEG
+/// images.
+fn read_metadata(
+    file_type: &FileType,
+    mime: &str,
+    contents: &[u8],
+    entries: Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) -> ExifData {
+    let irb = match file_type {
+        FileType::TIFF => find_photoshop_in_tiff(&entries).map(|irb| irb.to_vec()),
+        FileType::JPEG => find_photoshop_in_jpeg(contents),
+        FileType::PSD => find_photoshop_in_psd(contents).ok().map(|irb| irb.to_vec()),
+        _ => None,
+    };
+
+    let packet = match file_type {
+        FileType::TIFF => find_xmp_in_tiff(&entries).map(|p| p.to_vec()),
+        FileType::JPEG => find_xmp_in_jpeg(contents).map(|p| p.to_vec()),
+        FileType::PNG => find_xmp_in_png(contents),
+        FileType::WEBP => find_xmp_in_webp(contents).map(|p| p.to_vec()),
+        FileType::PSD => irb
+            .as_ref()
+            .and_then(|irb| find_resource(irb, XMP_RESOURCE))
+            .map(|p| p.to_vec()),
+        _ => None,
+    };
+    let mut xmp = packet.and_then(|p| read_xmp(&p, warnings));
+    if let (FileType::JPEG, Some(ref mut xmp)) = (file_type, &mut xmp) {
+        read_extended_xmp(contents, xmp, warnings);
+    }
 
-    // TODO: should read only the relevant parts of a file,
-    // and pass a StringIO-like object instead of a Vec buffer
+    let iptc = match file_type {
+        FileType::TIFF => find_iptc_in_tiff(&entries),
+        _ => irb
+            .as_ref()
+            .and_then(|irb| find_resource(irb, IPTC_RESOURCE)),
+    };
+    let iptc = iptc.and_then(|i| parse_iptc(i, warnings));
+    let icc = match file_type {
+        FileType::TIFF => Ok(find_icc_in_tiff(&entries).map(|p| p.to_vec())),
+        FileType::JPEG => find_icc_in_jpeg(contents),
+        FileType::PNG => Ok(find_icc_in_png(contents)),
+        FileType::WEBP => Ok(find_icc_in_webp(contents).map(|p| p.to_vec())),
+        FileType::PSD => Ok(irb
+            .as_ref()
+            .and_then(|irb| find_resource(irb, ICC_RESOURCE))
+            .map(|p| p.to_vec())),
+        _ => Ok(None),
+    };
+    let icc = match icc.and_then(|icc| icc.map(parse_icc).transpose()) {
+        Ok(icc) => icc,
+        Err(e) => {
+            warnings.push(e);
+            None
+        }
+    };
+
+    let photoshop = irb.and_then(|irb| parse_photoshop(&irb, warnings));
+
+    let mut mpf = match file_type {
+        FileType::JPEG => parse_mpf(contents, warnings),
+        _ => None,
+    };
+    if let Some(ref mut mpf) = mpf {
+        read_mp_attributes(contents, mpf, warnings);
+    }
 
-    let mut contents: Vec<u8> = Vec::new();
-    f.read_to_end(&mut contents)?;
-    parse_buffer(&contents)
+    ExifData {
+        mime: mime.to_string(),
+        xmp,
+        iptc,
+        photoshop,
+        icc,
+        mpf,
+        entries,
+    }
+}
+
+/// Parses an XMP packet, turning failures into warnings
+fn read_xmp(packet: &[u8], warnings: &mut Vec<String>) -> Option<XmpData> {
+    match parse_xmp(packet) {
+        Ok(xmp) => Some(xmp),
+        Err(e) => {
+            warnings.push(e);
+            None
+        }
+    }
+}
+
+/// Merges the extended XMP packet of a JPEG image, if the main packet refers to
+/// one, turning failures into warnings
+fn read_extended_xmp(contents: &[u8], xmp: &mut XmpData, warnings: &mut Vec<String>) {
+    let guid = match extended_xmp_guid(xmp) {
+        Some(guid) => guid.to_string(),
+        None => return,
+    };
+    let res = find_extended_xmp_in_jpeg(contents, &guid)
+        .and_then(|extended| merge_extended_xmp(xmp, &extended));
+    if let Err(e) = res {
+        warnings.push(e);
+    }
+}
+
+/// Parse an image read from a seekable stream, e.g. an open file, that should
+/// contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF or JPEG XL image, or a PSD file.
+/// Only the parts of the image that may contain EXIF data are read: the marker
+/// segments of a JPEG image up to the image data (and those of the further images
+/// of a Multi-Picture Format file), the IFDs of a TIFF image and the values they
+/// point to, or the image resources of a PSD file. Images of other formats are
+/// read whole.
+///
+/// Prints warnings to stderr.
+pub fn parse_reader<R: Read + Seek>(reader: R) -> ExifResult {
+    let (res, warnings) = parse_reader_quiet(reader);
+    warnings.into_iter().for_each(|w| eprintln!("{}", w));
+    res
+}
+
+/// Length of the start of a stream that is read to detect the type of the image.
+/// It covers the longest signature, and the brands of the ftyp box of HEIF images.
+const HEADER_LENGTH: u64 = 64;
+
+/// Parse an image read from a seekable stream, e.g. an open file, that should
+/// contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF or JPEG XL image, or a PSD file.
+/// See `parse_reader()`.
+///
+/// Returns warnings alongside result.
+pub fn parse_reader_quiet<R: Read + Seek>(mut reader: R) -> (ExifResult, Vec<String>) {
+    let mut warnings = vec![];
+
+    let mut header: Vec<u8> = Vec::new();
+    let res = reader
+        .seek(SeekFrom::Start(0))
+        .and_then(|_| reader.by_ref().take(HEADER_LENGTH).read_to_end(&mut header))
+        .and_then(|_| reader.seek(SeekFrom::Start(0)));
+    if let Err(e) = res {
+        return (Err(ExifError::IoError(e)), warnings);
+    }
+
+    match detect_type(&header) {
+        FileType::Unknown => (Err(ExifError::FileTypeUnknown), warnings),
+        FileType::TIFF => {
+            let res = parse_tiff_reader(reader, &mut warnings);
+            let res = read_metadata_of(&FileType::TIFF, "image/tiff", &[], res, &mut warnings);
+            (res, warnings)
+        }
+        FileType::JPEG => match read_jpeg_header(&mut reader) {
+            Ok(contents) => {
+                // secondary images of a Multi-Picture Format file are read
+                // only up to their image data, for their attributes
+                let (mut res, mut warnings) = parse_buffer_quiet(&contents);
+                if let Ok(ExifData {
+                    mpf: Some(ref mut mpf),
+                    ..
+                }) = res
+                {
+                    read_mp_attributes_from_reader(&mut reader, mpf, &mut warnings);
+                }
+                (res, warnings)
+            }
+            Err(e) => (Err(ExifError::IoError(e)), warnings),
+        },
+        FileType::PSD => match read_psd_header(&mut reader) {
+            Ok(contents) => parse_buffer_quiet(&contents),
+            Err(e) => (Err(ExifError::IoError(e)), warnings),
+        },
+        _ => {
+            let mut contents: Vec<u8> = Vec::new();
+            match reader.read_to_end(&mut contents) {
+                Ok(_) => parse_buffer_quiet(&contents),
+                Err(e) => (Err(ExifError::IoError(e)), warnings),
+            }
+        }
+    }
+}
+
+/// Try to read and parse an open file that is expected to contain an image
+pub fn read_file(f: &mut File) -> ExifResult {
+    parse_reader(f)
 }
 
 /// Opens an image (passed as a file name), tries to read and parse it.
@@ -1,6 +1,9 @@
 use super::rational::*;
 use std::mem;
 
+/// Upper limit of decompressed metadata, as a defense against zip bombs
+const MAX_INFLATED_SIZE: usize = 64 * 1024 * 1024;
+
 /// Read value from a stream of bytes
 #[inline]
 pub(crate) fn read_u16(le: bool, raw: &[u8]) -> u16 {
@@ -41,6 +44,20 @@ pub(crate) fn read_i32(le: bool, raw: &[u8]) -> i32 {
     u as i32
 }
 
+/// Read value from a stream of bytes
+#[inline]
+pub(crate) fn read_u64(le: bool, raw: &[u8]) -> u64 {
+    let hi = read_u32(le, &raw[if le { 4..8 } else { 0..4 }]) as u64;
+    let lo = read_u32(le, &raw[if le { 0..4 } else { 4..8 }]) as u64;
+    (hi << 32) + lo
+}
+
+/// Read value from a stream of bytes
+#[inline]
+pub(crate) fn read_i64(le: bool, raw: &[u8]) -> i64 {
+    read_u64(le, raw) as i64
+}
+
 /// Read value from a stream of bytes
 #[inline]
 pub(crate) fn read_f32(raw: &[u8]) -> f32 {
@@ -68,6 +85,36 @@ pub(crate) fn read_f64(raw: &[u8]) -> f64 {
     f
 }
 
+/// Write value into a stream of bytes
+#[inline]
+pub(crate) fn write_u16(le: bool, n: u16) -> [u8; 2] {
+    if le {
+        n.to_le_bytes()
+    } else {
+        n.to_be_bytes()
+    }
+}
+
+/// Write value into a stream of bytes
+#[inline]
+pub(crate) fn write_u32(le: bool, n: u32) -> [u8; 4] {
+    if le {
+        n.to_le_bytes()
+    } else {
+        n.to_be_bytes()
+    }
+}
+
+/// Write value into a stream of bytes
+#[inline]
+pub(crate) fn write_u64(le: bool, n: u64) -> [u8; 8] {
+    if le {
+        n.to_le_bytes()
+    } else {
+        n.to_be_bytes()
+    }
+}
+
 /// Read value from a stream of bytes
 #[inline]
 pub(crate) fn read_urational(le: bool, raw: &[u8]) -> URational {
@@ -143,6 +190,28 @@ pub(crate) fn read_i32_array(le: bool, count: u32, raw: &[u8]) -> Vec<i32> {
     a
 }
 
+/// Read array from a stream of bytes. Caller must be sure of count and buffer size
+pub(crate) fn read_u64_array(le: bool, count: u32, raw: &[u8]) -> Vec<u64> {
+    let mut a = Vec::<u64>::new();
+    let mut offset = 0;
+    for _ in 0..count {
+        a.push(read_u64(le, &raw[offset..offset + 8]));
+        offset += 8;
+    }
+    a
+}
+
+/// Read array from a stream of bytes. Caller must be sure of count and buffer size
+pub(crate) fn read_i64_array(le: bool, count: u32, raw: &[u8]) -> Vec<i64> {
+    let mut a = Vec::<i64>::new();
+    let mut offset = 0;
+    for _ in 0..count {
+        a.push(read_i64(le, &raw[offset..offset + 8]));
+        offset += 8;
+    }
+    a
+}
+
 /// Read array from a stream of bytes. Caller must be sure of count and buffer size
 pub(crate) fn read_f32_array(count: u32, raw: &[u8]) -> Vec<f32> {
     let mut a = Vec::<f32>::new();
@@ -186,3 +255,24 @@ pub(crate) fn read_irational_array(le: bool, count: u32, raw: &[u8]) -> Vec<IRat
     }
     a
 }
+
+/// Decompress a zlib stream, as found in PNG zTXt, iTXt and iCCP chunks
+pub(crate) fn inflate_zlib(data: &[u8]) -> Option<Vec<u8>> {
+    miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(data, MAX_INFLATED_SIZE).ok()
+}
+
+/// Decompress a Brotli stream, as found in the brob boxes of JPEG XL
+#[cfg(feature = "brotli")]
+pub(crate) fn inflate_brotli(data: &[u8]) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut out: Vec<u8> = Vec::new();
+    brotli_decompressor::Decompressor::new(data, 4096)
+        .take(MAX_INFLATED_SIZE as u64 + 1)
+        .read_to_end(&mut out)
+        .ok()?;
+    if out.len() > MAX_INFLATED_SIZE {
+        return None;
+    }
+    Some(out)
+}
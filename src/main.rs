@@ -25,6 +25,47 @@ fn main() {
                         println!("	{}: {}", entry.tag, entry.value_more_readable);
                     }
                 }
+                if let Some(ref xmp) = exif.xmp {
+                    println!("{} xmp properties: {}", arg, xmp.properties.len());
+                    for property in &xmp.properties {
+                        println!("	{}:{}: {}", property.prefix, property.name, property.value);
+                    }
+                }
+                if let Some(ref photoshop) = exif.photoshop {
+                    println!("{} photoshop resources: {}", arg, photoshop.resources.len());
+                }
+                if let Some(ref icc) = exif.icc {
+                    println!(
+                        "{} icc profile: {} {} {}",
+                        arg,
+                        icc.class,
+                        icc.color_space,
+                        icc.description.as_deref().unwrap_or("")
+                    );
+                }
+                if let Some(ref mpf) = exif.mpf {
+                    println!("{} mpf images: {}", arg, mpf.images.len());
+                    for image in &mpf.images {
+                        println!(
+                            "	type {:06x}: {} bytes at offset {}",
+                            image.image_type(),
+                            image.size,
+                            image.offset
+                        );
+                    }
+                }
+                if let Some(dng) = exif.dng() {
+                    println!(
+                        "{} dng version {:?}, raw image in {:?}, crop {:?}",
+                        arg, dng.version, dng.raw_ifd, dng.default_crop_size
+                    );
+                }
+                if let Some(ref iptc) = exif.iptc {
+                    println!("{} iptc datasets: {}", arg, iptc.datasets.len());
+                    for dataset in &iptc.datasets {
+                        println!("	{}: {}", dataset.tag, dataset.value);
+                    }
+                }
             }
             Err(e) => {
                 eprintln!("Error in {}: {}", &arg, e);
@@ -0,0 +1,174 @@
+use super::image::{jpeg_segments, read_jpeg_header};
+use super::lowlevel::*;
+use super::tiff::*;
+use super::types::*;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Identifier that opens the APP2 segment of a JPEG image that contains the
+/// Multi-Picture Format data
+static MPF_JPEG_ID: &[u8] = b"MPF\0";
+
+/// MPEntry tag of the MP Index IFD, that lists the images
+const MP_ENTRY_TAG: u16 = 0xb002;
+
+/// Size of the record of each image within the MPEntry tag
+const MP_ENTRY_SIZE: usize = 16;
+
+/// Find the MPF segment of a JPEG image. Returns the offset of the MP header
+/// within the image, and the contents of the segment from the MP header on. The
+/// MP header is a TIFF header, and offsets within the segment are relative to it.
+fn find_mpf_in_jpeg(contents: &[u8]) -> Option<(usize, &[u8])> {
+    jpeg_segments(contents)
+        .map_while(Result::ok)
+        .find(|s| s.marker == 0xffe2 && s.data.starts_with(MPF_JPEG_ID))
+        .map(|s| {
+            let header = MPF_JPEG_ID.len();
+            (s.offset + 4 + header, &s.data[header..])
+        })
+}
+
+/// Parses an IFD of the MPF segment, returning its entries and the offset of
+/// the next IFD. The MP Attribute IFDs of secondary images may lack the next
+/// IFD offset, since nothing follows them.
+fn parse_mpf_ifd(
+    ifd_kind: IfdKind,
+    le: bool,
+    mpf: &[u8],
+    offset: usize,
+    warnings: &mut Vec<String>,
+) -> Option<(Vec<ExifEntry>, usize)> {
+    let count = read_u16(le, mpf.get(offset..offset + 2)?);
+    let ifd_length = (count as usize) * 12;
+    let ifd_content = mpf.get(offset + 2..offset + 2 + ifd_length)?;
+    let (mut ifd, _) = parse_ifd(true, ifd_kind, le, false, count as usize, ifd_content)?;
+    let next_ifd = mpf
+        .get(offset + 2 + ifd_length..offset + 6 + ifd_length)
+        .map_or(0, |next| read_u32(le, next) as usize);
+
+    let mut source = mpf;
+    let mut entries: Vec<ExifEntry> = Vec::new();
+    for entry in &mut ifd {
+        if entry.copy_data_from(&mut source) {
+            entries.push(parse_exif_entry(entry, warnings));
+        }
+    }
+    Some((entries, next_ifd))
+}
+
+/// Decodes the records of the MPEntry tag. Offsets of images are relative to
+/// the MP header, and made absolute with `mpf_offset`, except for the primary
+/// image, whose offset is 0.
+fn mp_images(entry: &ExifEntry, mpf_offset: usize) -> Vec<MpImage> {
+    let le = entry.ifd.le;
+    entry
+        .ifd
+        .data
+        .chunks_exact(MP_ENTRY_SIZE)
+        .map(|record| {
+            let offset = read_u32(le, &record[8..12]) as usize;
+            MpImage {
+                attribute: read_u32(le, &record[0..4]),
+                size: read_u32(le, &record[4..8]),
+                offset: if offset == 0 {
+                    0
+                } else {
+                    mpf_offset.saturating_add(offset)
+                },
+                dependent_images: [read_u16(le, &record[12..14]), read_u16(le, &record[14..16])],
+                attributes: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Parses the Multi-Picture Format data of a JPEG image: the MP Index IFD, that
+/// lists the images, and the MP Attribute IFD of the primary image that follows.
+/// The attributes of secondary images are read afterwards, from the images
+/// themselves.
+///
+/// Problems are reported as warnings, since they do not affect the EXIF data.
+pub(crate) fn parse_mpf(contents: &[u8], warnings: &mut Vec<String>) -> Option<MpfData> {
+    let (mpf_offset, mpf) = find_mpf_in_jpeg(contents)?;
+    let (le, offset) = match parse_tiff_header(mpf) {
+        Ok((le, false, offset)) => (le, offset),
+        Ok(_) => {
+            warnings.push("MPF header unrecognized: BigTIFF".to_string());
+            return None;
+        }
+        Err(e) => {
+            warnings.push(format!("MPF header unrecognized: {}", e));
+            return None;
+        }
+    };
+
+    let (entries, next_ifd) = match parse_mpf_ifd(IfdKind::MpIndex, le, mpf, offset, warnings) {
+        Some(ifd) => ifd,
+        None => {
+            warnings.push("MP Index IFD truncated".to_string());
+            return None;
+        }
+    };
+    let mut images = match entries.iter().find(|e| e.ifd.tag == MP_ENTRY_TAG) {
+        Some(entry) => mp_images(entry, mpf_offset),
+        None => Vec::new(),
+    };
+
+    if let (Some(primary), true) = (images.first_mut(), next_ifd != 0) {
+        match parse_mpf_ifd(IfdKind::MpAttributes, le, mpf, next_ifd, warnings) {
+            Some((attributes, _)) => primary.attributes = attributes,
+            None => warnings.push("MP Attribute IFD truncated".to_string()),
+        }
+    }
+
+    Some(MpfData { entries, images })
+}
+
+/// Parses the MP Attribute IFD of a secondary image, that is the first IFD of
+/// the MPF segment of the image itself
+fn parse_mp_attributes(image: &[u8], warnings: &mut Vec<String>) -> Vec<ExifEntry> {
+    let attributes = find_mpf_in_jpeg(image).and_then(|(_, mpf)| {
+        let (le, offset) = match parse_tiff_header(mpf).ok()? {
+            (le, false, offset) => (le, offset),
+            _ => return None,
+        };
+        parse_mpf_ifd(IfdKind::MpAttributes, le, mpf, offset, warnings)
+    });
+    match attributes {
+        Some((attributes, _)) => attributes,
+        None => Vec::new(),
+    }
+}
+
+/// Fills the MP Attribute IFDs of the secondary images found in a buffer. Images
+/// past the end of the buffer are skipped.
+pub(crate) fn read_mp_attributes(contents: &[u8], mpf: &mut MpfData, warnings: &mut Vec<String>) {
+    for image in mpf.images.iter_mut().filter(|i| i.offset != 0) {
+        if let Some(data) = contents.get(image.range()) {
+            image.attributes = parse_mp_attributes(data, warnings);
+        }
+    }
+}
+
+/// Fills the MP Attribute IFDs of the secondary images of a JPEG file read from
+/// a seekable stream. Only the marker segments of each image are read.
+pub(crate) fn read_mp_attributes_from_reader<R: Read + Seek>(
+    reader: &mut R,
+    mpf: &mut MpfData,
+    warnings: &mut Vec<String>,
+) {
+    for image in mpf.images.iter_mut().filter(|i| i.offset != 0) {
+        let res = reader
+            .seek(SeekFrom::Start(image.offset as u64))
+            .and_then(|_| read_jpeg_header(&mut reader.by_ref().take(image.size as u64)));
+        match res {
+            Ok(data) => image.attributes = parse_mp_attributes(&data, warnings),
+            Err(e) => {
+                warnings.push(format!(
+                    "MPF image at offset {} unreadable: {}",
+                    image.offset, e
+                ));
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::lowlevel::*;
+use super::types::*;
+use std::io;
+use std::io::Read;
+
+/// Identifiers of the resources that are decoded, or that contain metadata
+/// parsed elsewhere
+const RESOLUTION_RESOURCE: u16 = 1005;
+pub(crate) const IPTC_RESOURCE: u16 = 1028;
+const THUMBNAIL_BGR_RESOURCE: u16 = 1033;
+const COPYRIGHT_RESOURCE: u16 = 1034;
+const URL_RESOURCE: u16 = 1035;
+const THUMBNAIL_RESOURCE: u16 = 1036;
+pub(crate) const ICC_RESOURCE: u16 = 1039;
+const SLICES_RESOURCE: u16 = 1050;
+pub(crate) const EXIF_RESOURCE: u16 = 1058;
+pub(crate) const XMP_RESOURCE: u16 = 1060;
+
+/// TIFF tag that contains the image resource block, in IFD0
+const PHOTOSHOP_TAG: u16 = 34377;
+
+/// Resource of a Photoshop image resource block (IRB)
+pub(crate) struct IrbResource<'a> {
+    /// Resource identifier, e.g. 1028 for IPTC-IIM
+    pub id: u16,
+    /// Name of the resource, usually empty
+    pub name: &'a [u8],
+    /// Contents of the resource, excluding padding
+    pub data: &'a [u8],
+    /// Offset of the contents within the block
+    pub offset: usize,
+}
+
+/// Iterator over the resources of a Photoshop image resource block, as found
+/// in the APP13 segment of a JPEG image, in TIFF tag 34377, or in PSD files
+pub(crate) struct IrbResources<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+pub(crate) fn irb_resources(contents: &[u8]) -> IrbResources<'_> {
+    IrbResources {
+        contents,
+        offset: 0,
+        done: false,
+    }
+}
+
+impl<'a> IrbResources<'a> {
+    fn read_resource(&self) -> Result<(IrbResource<'a>, usize), &'static str> {
+        let contents = self.contents;
+        let offset = self.offset;
+
+        let header = contents
+            .get(offset..offset + 6)
+            .ok_or("Photoshop resource truncated in header")?;
+        // signature is "8BIM" but for a few resources written by old software
+        if !matches!(
+            &header[0..4],
+            b"8BIM" | b"PHUT" | b"AgHg" | b"DCSR" | b"MeSa"
+        ) {
+            return Err("Photoshop resource signature unrecognized");
+        }
+        let id = read_u16(false, &header[4..6]);
+
+        // name is a Pascal string, padded to an even length
+        let name_length = *contents
+            .get(offset + 6)
+            .ok_or("Photoshop resource truncated in name")? as usize;
+        let name = contents
+            .get(offset + 7..offset + 7 + name_length)
+            .ok_or("Photoshop resource truncated in name")?;
+        let size_offset = offset + 6 + ((1 + name_length + 1) & !1);
+
+        let size = contents
+            .get(size_offset..size_offset + 4)
+            .ok_or("Photoshop resource truncated in size")?;
+        let size = read_u32(false, size) as usize;
+        let data_offset = size_offset + 4;
+        let data = contents
+            .get(data_offset..data_offset.saturating_add(size))
+            .ok_or("Photoshop resource truncated in data")?;
+
+        // data is padded to an even length, but the last padding byte may be missing
+        let end = data_offset + size + size % 2;
+        let resource = IrbResource {
+            id,
+            name,
+            data,
+            offset: data_offset,
+        };
+        Ok((resource, end))
+    }
+}
+
+impl<'a> Iterator for IrbResources<'a> {
+    type Item = Result<IrbResource<'a>, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.contents.len() {
+            return None;
+        }
+        // some writers pad the block with zeros
+        if self.contents[self.offset..].iter().all(|&b| b == 0) {
+            return None;
+        }
+
+        match self.read_resource() {
+            Ok((resource, end)) => {
+                self.offset = end;
+                Some(Ok(resource))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Finds the contents of a resource within an image resource block
+pub(crate) fn find_resource(irb: &[u8], id: u16) -> Option<&[u8]> {
+    irb_resources(irb)
+        .map_while(Result::ok)
+        .find(|r| r.id == id)
+        .map(|r| r.data)
+}
+
+/// Finds the image resource block of a TIFF image, stored in IFD0
+pub(crate) fn find_photoshop_in_tiff(entries: &[ExifEntry]) -> Option<&[u8]> {
+    entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == IfdKind::Ifd0 && e.ifd.tag == PHOTOSHOP_TAG)
+        .map(|e| &e.ifd.data[..])
+}
+
+/// Returns true if the buffer starts with the header of a PSD (or PSB, for
+/// large documents) file
+pub(crate) fn is_psd(contents: &[u8]) -> bool {
+    contents.len() >= 6 && &contents[0..4] == b"8BPS" && (contents[5] == 1 || contents[5] == 2)
+}
+
+/// Length of the header of a PSD file, followed by the color mode data section
+const PSD_HEADER_LENGTH: usize = 26;
+
+/// Finds the image resource block of a PSD file, that follows the header and
+/// the color mode data
+pub(crate) fn find_photoshop_in_psd(contents: &[u8]) -> Result<&[u8], ExifError> {
+    let truncated = || ExifError::PsdWithoutExif("PSD truncated in header".to_string());
+    let section_length = |offset: usize| -> Result<usize, ExifError> {
+        let length = contents.get(offset..offset + 4).ok_or_else(truncated)?;
+        Ok(read_u32(false, length) as usize)
+    };
+
+    let color_mode_length = section_length(PSD_HEADER_LENGTH)?;
+    let irb_offset = PSD_HEADER_LENGTH + 4 + color_mode_length;
+    let irb_length = section_length(irb_offset)?;
+    contents
+        .get(irb_offset + 4..irb_offset + 4 + irb_length)
+        .ok_or_else(|| ExifError::PsdWithoutExif("PSD truncated in image resources".to_string()))
+}
+
+/// Reads the start of a PSD file, up to the end of the image resources section,
+/// so that it can be walked by `find_photoshop_in_psd()`. Layers and image data
+/// are not read.
+pub(crate) fn read_psd_header<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut contents: Vec<u8> = Vec::new();
+    let header_length = PSD_HEADER_LENGTH as u64;
+    if reader
+        .by_ref()
+        .take(header_length)
+        .read_to_end(&mut contents)?
+        < PSD_HEADER_LENGTH
+    {
+        return Ok(contents);
+    }
+
+    // color mode data and image resources, each preceded by its length
+    for _ in 0..2 {
+        let offset = contents.len();
+        if reader.by_ref().take(4).read_to_end(&mut contents)? < 4 {
+            break;
+        }
+        let length = read_u32(false, &contents[offset..offset + 4]) as u64;
+        if (reader.by_ref().take(length).read_to_end(&mut contents)? as u64) < length {
+            break;
+        }
+    }
+    Ok(contents)
+}
+
+/// Decodes a Unicode string of a Photoshop resource: a count of UTF-16 code
+/// units, followed by the units in big-endian byte order
+fn read_unicode(data: &[u8], offset: &mut usize) -> Option<String> {
+    let count = read_u32(false, data.get(*offset..*offset + 4)?) as usize;
+    let units = data.get(*offset + 4..(*offset + 4).checked_add(count.checked_mul(2)?)?)?;
+    *offset += 4 + count * 2;
+    let units: Vec<u16> = units.chunks(2).map(|u| read_u16(false, u)).collect();
+    Some(
+        String::from_utf16_lossy(&units)
+            .trim_end_matches('\0')
+            .to_string(),
+    )
+}
+
+/// Resolution info: horizontal and vertical resolution as 16.16 fixed-point
+/// numbers, each followed by its unit and the unit of the image size
+fn decode_resolution(data: &[u8]) -> Option<PhotoshopValue> {
+    let data = data.get(0..16)?;
+    let fixed = |raw: &[u8]| read_u32(false, raw) as f64 / 65536.0;
+    Some(PhotoshopValue::Resolution(PhotoshopResolution {
+        x: fixed(&data[0..4]),
+        x_unit: read_u16(false, &data[4..6]),
+        y: fixed(&data[8..12]),
+        y_unit: read_u16(false, &data[12..14]),
+    }))
+}
+
+/// Thumbnail: a header with the format and dimensions, followed by the
+/// JFIF data
+fn decode_thumbnail(data: &[u8], bgr: bool) -> Option<PhotoshopValue> {
+    let header = data.get(0..28)?;
+    Some(PhotoshopValue::Thumbnail(PhotoshopThumbnail {
+        format: read_u32(false, &header[0..4]),
+        width: read_u32(false, &header[4..8]),
+        height: read_u32(false, &header[8..12]),
+        bits_per_pixel: read_u16(false, &header[24..26]),
+        bgr,
+        data: data[28..].to_vec(),
+    }))
+}
+
+/// Slices, in the format of Photoshop 6: the bounds and name of the group,
+/// followed by the slices. Later formats are made of descriptors, and are
+/// not decoded.
+fn decode_slices(data: &[u8]) -> Option<PhotoshopValue> {
+    if read_u32(false, data.get(0..4)?) != 6 {
+        return None;
+    }
+    let bounds = data.get(4..20)?;
+    let bound = |i: usize| read_u32(false, &bounds[i * 4..i * 4 + 4]) as i32;
+    let mut offset = 20;
+    let group_name = read_unicode(data, &mut offset)?;
+    let count = read_u32(false, data.get(offset..offset + 4)?);
+    Some(PhotoshopValue::Slices(PhotoshopSlices {
+        top: bound(0),
+        left: bound(1),
+        bottom: bound(2),
+        right: bound(3),
+        group_name,
+        count,
+    }))
+}
+
+fn decode_resource(id: u16, data: &[u8]) -> Option<PhotoshopValue> {
+    match id {
+        RESOLUTION_RESOURCE => decode_resolution(data),
+        THUMBNAIL_BGR_RESOURCE => decode_thumbnail(data, true),
+        THUMBNAIL_RESOURCE => decode_thumbnail(data, false),
+        COPYRIGHT_RESOURCE => Some(PhotoshopValue::CopyrightFlag(*data.first()? != 0)),
+        URL_RESOURCE => Some(PhotoshopValue::Url(
+            String::from_utf8_lossy(data)
+                .trim_end_matches('\0')
+                .to_string(),
+        )),
+        SLICES_RESOURCE => decode_slices(data),
+        _ => None,
+    }
+}
+
+/// Decodes the resources of an image resource block. Resources found before
+/// a malformed one are kept, and the problem is reported as a warning.
+pub(crate) fn parse_photoshop(irb: &[u8], warnings: &mut Vec<String>) -> Option<PhotoshopData> {
+    let mut resources: Vec<PhotoshopResource> = Vec::new();
+    for resource in irb_resources(irb) {
+        let resource = match resource {
+            Ok(resource) => resource,
+            Err(e) => {
+                warnings.push(e.to_string());
+                break;
+            }
+        };
+        resources.push(PhotoshopResource {
+            id: resource.id,
+            name: String::from_utf8_lossy(resource.name).into_owned(),
+            data: resource.data.to_vec(),
+            value: decode_resource(resource.id, resource.data).unwrap_or(PhotoshopValue::Raw),
+        });
+    }
+
+    if resources.is_empty() {
+        None
+    } else {
+        Some(PhotoshopData { resources })
+    }
+}
@@ -0,0 +1,186 @@
+use super::image::strip_exif_preamble;
+use super::lowlevel::*;
+use super::types::*;
+
+/// Signature at the start of every PNG image
+pub(crate) static PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Keyword of the tEXt/zTXt chunks where ImageMagick and older tools store
+/// EXIF data, hex-encoded, before the eXIf chunk was standardized
+static RAW_PROFILE_EXIF: &[u8] = b"Raw profile type exif";
+
+/// Keyword of the iTXt chunk that contains XMP
+static XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+
+/// Chunk of a PNG image
+pub(crate) struct PngChunk<'a> {
+    /// Chunk type, e.g. b"eXIf"
+    pub kind: &'a [u8],
+    /// Contents of the chunk, excluding length, type and CRC
+    pub data: &'a [u8],
+}
+
+/// Iterator over the chunks of a PNG image, up to IEND
+pub(crate) struct PngChunks<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+pub(crate) fn png_chunks(contents: &[u8]) -> PngChunks<'_> {
+    PngChunks {
+        contents,
+        offset: PNG_SIGNATURE.len(),
+        done: false,
+    }
+}
+
+impl<'a> PngChunks<'a> {
+    fn read_chunk(&self) -> Result<PngChunk<'a>, ExifError> {
+        let contents = self.contents;
+        let offset = self.offset;
+
+        if contents.len() < (offset + 8) {
+            return Err(ExifError::PngWithoutExif(
+                "PNG truncated in chunk header".to_string(),
+            ));
+        }
+
+        let length = read_u32(false, &contents[offset..offset + 4]) as usize;
+        let kind = &contents[offset + 4..offset + 8];
+
+        // data is followed by CRC
+        if contents.len() - (offset + 8) < length + 4 {
+            return Err(ExifError::PngWithoutExif(
+                "PNG truncated in chunk body".to_string(),
+            ));
+        }
+
+        Ok(PngChunk {
+            kind,
+            data: &contents[offset + 8..offset + 8 + length],
+        })
+    }
+}
+
+impl<'a> Iterator for PngChunks<'a> {
+    type Item = Result<PngChunk<'a>, ExifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.contents.len() {
+            return None;
+        }
+
+        let chunk = self.read_chunk();
+        match chunk {
+            Ok(ref chunk) => {
+                self.offset += 8 + chunk.data.len() + 4;
+                self.done = chunk.kind == b"IEND";
+            }
+            Err(_) => self.done = true,
+        }
+        Some(chunk)
+    }
+}
+
+/// Splits a tEXt chunk, or the uncompressed contents of zTXt, into keyword and text
+fn split_keyword(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let nul = data.iter().position(|&b| b == 0)?;
+    Some((&data[..nul], &data[nul + 1..]))
+}
+
+/// Decodes an ImageMagick raw profile, e.g. "\nexif\n    1234\n4578696600004d4d...",
+/// where the hex digits are broken in lines
+fn decode_raw_profile(text: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(text).ok()?;
+    let mut lines = text.trim_start().splitn(3, '\n');
+    let _name = lines.next()?;
+    let length: usize = lines.next()?.trim().parse().ok()?;
+    let hex = lines.next()?;
+
+    let mut profile: Vec<u8> = Vec::with_capacity(length.min(hex.len() / 2));
+    let mut digits = hex
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| (b as char).to_digit(16));
+    while profile.len() < length {
+        let high = digits.next()??;
+        let low = digits.next()??;
+        profile.push((high * 16 + low) as u8);
+    }
+    Some(profile)
+}
+
+/// Decodes the EXIF data of a legacy tEXt or zTXt chunk, if it is one
+fn legacy_exif_chunk(chunk: &PngChunk<'_>) -> Option<Vec<u8>> {
+    let (keyword, text) = split_keyword(chunk.data)?;
+    if keyword != RAW_PROFILE_EXIF {
+        return None;
+    }
+    let profile = if chunk.kind == b"zTXt" {
+        // compression method byte, always 0 (zlib)
+        let compressed = text.get(1..)?;
+        decode_raw_profile(&inflate_zlib(compressed)?)?
+    } else {
+        decode_raw_profile(text)?
+    };
+    Some(strip_exif_preamble(&profile).to_vec())
+}
+
+/// Find the embedded TIFF in a PNG image (that in turn contains the EXIF data).
+///
+/// The standard eXIf chunk is preferred. Failing that, the EXIF data is taken
+/// from a "Raw profile type exif" tEXt or zTXt chunk, the hex-encoded format
+/// written by ImageMagick and older tools.
+pub fn find_embedded_tiff_in_png(contents: &[u8]) -> Result<Vec<u8>, ExifError> {
+    let mut legacy: Option<Vec<u8>> = None;
+
+    for chunk in png_chunks(contents) {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            // truncated image, but legacy EXIF data may have been found already
+            Err(e) => return legacy.ok_or(e),
+        };
+
+        if chunk.kind == b"eXIf" {
+            return Ok(strip_exif_preamble(chunk.data).to_vec());
+        }
+        if legacy.is_none() && (chunk.kind == b"tEXt" || chunk.kind == b"zTXt") {
+            legacy = legacy_exif_chunk(&chunk);
+        }
+    }
+
+    legacy.ok_or_else(|| ExifError::PngWithoutExif("No eXIf chunk found".to_string()))
+}
+
+/// Find the XMP packet of a PNG image, stored in an iTXt chunk that may be
+/// compressed
+pub(crate) fn find_xmp_in_png(contents: &[u8]) -> Option<Vec<u8>> {
+    let chunk = png_chunks(contents).map_while(Result::ok).find(|chunk| {
+        chunk.kind == b"iTXt" && split_keyword(chunk.data).map(|(k, _)| k) == Some(XMP_KEYWORD)
+    })?;
+    let (_, rest) = split_keyword(chunk.data)?;
+
+    // compression flag and method, then language tag and translated keyword
+    let compressed = *rest.first()? != 0;
+    let mut fields = rest.get(2..)?.splitn(3, |&b| b == 0);
+    let _lang = fields.next()?;
+    let _translated = fields.next()?;
+    let text = fields.next()?;
+    if compressed {
+        inflate_zlib(text)
+    } else {
+        Some(text.to_vec())
+    }
+}
+
+/// Find the ICC profile of a PNG image, stored compressed in the iCCP chunk
+/// after the name of the profile
+pub(crate) fn find_icc_in_png(contents: &[u8]) -> Option<Vec<u8>> {
+    let chunk = png_chunks(contents)
+        .map_while(Result::ok)
+        .find(|chunk| chunk.kind == b"iCCP")?;
+    let (_, rest) = split_keyword(chunk.data)?;
+    // compression method byte, always 0 (zlib)
+    inflate_zlib(rest.get(1..)?)
+}
@@ -0,0 +1,469 @@
+use super::image::*;
+use super::lowlevel::*;
+use super::photoshop::*;
+use super::tiff::*;
+use super::types::*;
+use super::xmp::*;
+use std::ops::Range;
+
+/// Groups of metadata that `strip()` can remove from an image
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StripPreset {
+    /// All EXIF data. JPEG images lose the whole EXIF segment. TIFF images keep
+    /// the tags that describe the image itself, and lose the Exif SubIFD, the GPS
+    /// IFD and the descriptive tags of IFD0 (make, model, dates, artist, etc.)
+    /// The XMP properties removed by `Gps` and `SerialNumbers` go as well.
+    AllExif,
+    /// The GPS IFD, that contains the location where the image was taken, and
+    /// the GPS properties of the XMP packet
+    Gps,
+    /// Serial numbers of camera body and lens, and the camera owner name, along
+    /// with the XMP properties that hold them. Those of the Canon MakerNote go as
+    /// well, those of other MakerNotes need `MakerNote`.
+    SerialNumbers,
+    /// The manufacturer-specific MakerNote blob
+    MakerNote,
+}
+
+/// Descriptive IFD0 tags removed by `StripPreset::AllExif`
+static IFD0_METADATA_TAGS: [u16; 15] = [
+    0x010e, // ImageDescription
+    0x010f, // Make
+    0x0110, // Model
+    0x0131, // Software
+    0x0132, // DateTime
+    0x013b, // Artist
+    0x013c, // HostComputer
+    0x4746, // Rating
+    0x4749, // RatingPercent
+    0x8298, // Copyright
+    0x9c9b, // XPTitle
+    0x9c9c, // XPComment
+    0x9c9d, // XPAuthor
+    0x9c9e, // XPKeywords
+    0x9c9f, // XPSubject
+];
+
+/// Exif SubIFD tags removed by `StripPreset::SerialNumbers`
+static EXIF_SERIAL_TAGS: [u16; 3] = [
+    0xa430, // CameraOwnerName
+    0xa431, // BodySerialNumber
+    0xa435, // LensSerialNumber
+];
+
+/// IFD0 tags removed by `StripPreset::SerialNumbers`
+static IFD0_SERIAL_TAGS: [u16; 1] = [
+    0xc62f, // CameraSerialNumber (DNG)
+];
+
+/// Canon MakerNote tags removed by `StripPreset::SerialNumbers`
+static CANON_SERIAL_TAGS: [u16; 3] = [
+    0x0009, // OwnerName
+    0x000c, // SerialNumber
+    0x0096, // InternalSerialNumber
+];
+
+/// Namespaces of the XMP properties that hold EXIF data
+static XMP_EXIF_NS: &str = "http://ns.adobe.com/exif/1.0/";
+static XMP_EXIF_EX_NS: &str = "http://cipa.jp/exif/1.0/";
+static XMP_AUX_NS: &str = "http://ns.adobe.com/exif/1.0/aux/";
+
+/// XMP properties removed by `StripPreset::SerialNumbers`
+static XMP_SERIAL_PROPERTIES: [(&str, &str); 6] = [
+    (XMP_AUX_NS, "SerialNumber"),
+    (XMP_AUX_NS, "LensSerialNumber"),
+    (XMP_AUX_NS, "OwnerName"),
+    (XMP_EXIF_EX_NS, "BodySerialNumber"),
+    (XMP_EXIF_EX_NS, "LensSerialNumber"),
+    (XMP_EXIF_EX_NS, "CameraOwnerName"),
+];
+
+/// How deep sub-IFDs are followed when erased. Protects against offset loops.
+const MAX_IFD_DEPTH: u32 = 4;
+
+fn tag_of(tag: ExifTag) -> u16 {
+    ((tag as u32) & 0xffff) as u16
+}
+
+/// Tags whose value is the offset of another IFD
+fn is_sub_ifd(tag: u16) -> bool {
+    tag == tag_of(ExifTag::ExifOffset)
+        || tag == tag_of(ExifTag::GPSOffset)
+        || tag == tag_of(ExifTag::InteroperabilityOffset)
+}
+
+/// Reads the entry count of an IFD, 16-bit in classic TIFF and 64-bit in BigTIFF
+fn read_count(tiff: &[u8], le: bool, big: bool, offset: usize) -> Result<usize, ExifError> {
+    let (count_size, _, _) = ifd_layout(big);
+    let count = offset
+        .checked_add(count_size)
+        .and_then(|end| tiff.get(offset..end))
+        .ok_or(ExifError::IfdTruncated)?;
+    Ok(read_word(le, count))
+}
+
+/// Reads the entries of the IFD at `offset`, without their out-of-line data
+fn read_ifd(tiff: &[u8], le: bool, big: bool, offset: usize) -> Result<Vec<IfdEntry>, ExifError> {
+    let (count_size, entry_size, _) = ifd_layout(big);
+    let count = read_count(tiff, le, big, offset)?;
+    let start = offset
+        .checked_add(count_size)
+        .ok_or(ExifError::IfdTruncated)?;
+    let ifd_content = count
+        .checked_mul(entry_size)
+        .and_then(|length| tiff.get(start..start.checked_add(length)?))
+        .ok_or(ExifError::IfdTruncated)?;
+    let (entries, _) = parse_ifd(true, IfdKind::Ifd0, le, big, count, ifd_content)
+        .ok_or(ExifError::IfdTruncated)?;
+    Ok(entries)
+}
+
+fn zero(tiff: &mut [u8], start: usize, end: usize) {
+    let end = end.min(tiff.len());
+    if start < end {
+        for b in &mut tiff[start..end] {
+            *b = 0;
+        }
+    }
+}
+
+/// Overwrites the out-of-line data of an entry with zeros
+fn erase_data(tiff: &mut [u8], entry: &IfdEntry) {
+    if !entry.in_ifd() {
+        let offset = entry.data_as_offset();
+        zero(tiff, offset, offset.saturating_add(entry.length()));
+    }
+}
+
+/// Overwrites a whole IFD with zeros, along with its data and sub-IFDs.
+/// Best effort: IFDs that can't be read are left alone, the caller
+/// removes the entry that points to them anyway.
+fn erase_ifd(tiff: &mut [u8], le: bool, big: bool, offset: usize, depth: u32) {
+    if depth > MAX_IFD_DEPTH {
+        return;
+    }
+    let entries = match read_ifd(tiff, le, big, offset) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in &entries {
+        if is_sub_ifd(entry.tag) {
+            erase_ifd(tiff, le, big, entry.data_as_offset(), depth + 1);
+        }
+        erase_data(tiff, entry);
+    }
+    // entry count, entries and next IFD offset
+    let (count_size, entry_size, offset_size) = ifd_layout(big);
+    let length = count_size + entries.len() * entry_size + offset_size;
+    zero(tiff, offset, offset.saturating_add(length));
+}
+
+/// Removes the entries selected by `remove` from the IFD at `offset`, erasing
+/// their data and the sub-IFDs they point to. Remaining entries are moved up,
+/// so the IFD itself does not change place.
+fn remove_entries<F>(
+    tiff: &mut [u8],
+    le: bool,
+    big: bool,
+    offset: usize,
+    remove: F,
+) -> Result<(), ExifError>
+where
+    F: Fn(u16) -> bool,
+{
+    let (count_size, entry_size, offset_size) = ifd_layout(big);
+    let entries = read_ifd(tiff, le, big, offset)?;
+    // entries whose count does not fit in 32 bits are not returned by read_ifd()
+    if entries.len() != read_count(tiff, le, big, offset)? {
+        return Err(ExifError::IfdTruncated);
+    }
+    let start = offset
+        .checked_add(count_size)
+        .ok_or(ExifError::IfdTruncated)?;
+    let end = start
+        .checked_add(entries.len() * entry_size)
+        .ok_or(ExifError::IfdTruncated)?;
+    let mut kept: Vec<u8> = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if remove(entry.tag) {
+            if is_sub_ifd(entry.tag) {
+                erase_ifd(tiff, le, big, entry.data_as_offset(), 1);
+            }
+            erase_data(tiff, entry);
+        } else {
+            let entry_start = start + i * entry_size;
+            kept.extend_from_slice(&tiff[entry_start..entry_start + entry_size]);
+        }
+    }
+
+    if kept.len() == end - start {
+        return Ok(());
+    }
+
+    // the next IFD offset follows the entries, but some writers omit it in sub-IFDs
+    let next_ifd = tiff.get(end..end + offset_size).map(|n| n.to_vec());
+    zero(tiff, start, end + offset_size);
+    let count = kept.len() / entry_size;
+    if big {
+        tiff[offset..start].copy_from_slice(&write_u64(le, count as u64));
+    } else {
+        tiff[offset..start].copy_from_slice(&write_u16(le, count as u16));
+    }
+    tiff[start..start + kept.len()].copy_from_slice(&kept);
+    if let Some(next_ifd) = next_ifd {
+        let next_offset = start + kept.len();
+        tiff[next_offset..next_offset + offset_size].copy_from_slice(&next_ifd);
+    }
+
+    Ok(())
+}
+
+/// Finds the offset of a sub-IFD pointed by an entry of the IFD at `offset`
+fn find_sub_ifd(tiff: &[u8], le: bool, big: bool, offset: usize, tag: ExifTag) -> Option<usize> {
+    let entries = read_ifd(tiff, le, big, offset).ok()?;
+    entries
+        .iter()
+        .find(|e| e.tag == tag_of(tag))
+        .map(|e| e.data_as_offset())
+}
+
+/// Returns true if the Make tag of IFD0 names Canon, whose MakerNote is an IFD
+/// with offsets relative to the TIFF header
+fn is_canon(tiff: &[u8], le: bool, big: bool, ifd0: usize) -> bool {
+    let entries = match read_ifd(tiff, le, big, ifd0) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    let make = match entries.iter().find(|e| e.tag == tag_of(ExifTag::Make)) {
+        Some(make) => make,
+        None => return false,
+    };
+    let value = if make.in_ifd() {
+        Some(&make.ifd_data[..])
+    } else {
+        let offset = make.data_as_offset();
+        tiff.get(offset..offset.saturating_add(make.length()))
+    };
+    matches!(value, Some(v) if v.starts_with(b"Canon"))
+}
+
+/// Returns true if an XMP property is removed by one of the presets. The GPS
+/// properties are those of the exif namespace whose name starts with GPS.
+fn is_xmp_stripped(presets: &[StripPreset], namespace: &str, name: &str) -> bool {
+    presets.iter().any(|preset| match *preset {
+        StripPreset::AllExif | StripPreset::Gps
+            if namespace == XMP_EXIF_NS && name.starts_with("GPS") =>
+        {
+            true
+        }
+        StripPreset::AllExif | StripPreset::SerialNumbers => {
+            XMP_SERIAL_PROPERTIES.contains(&(namespace, name))
+        }
+        _ => false,
+    })
+}
+
+/// Returns false if none of the presets removes XMP properties, since the
+/// MakerNote has no counterpart in XMP
+fn strips_xmp(presets: &[StripPreset]) -> bool {
+    presets.iter().any(|&p| p != StripPreset::MakerNote)
+}
+
+/// Removes properties in place from an XMP packet, by overwriting them with
+/// spaces, so the packet stays valid XML. A packet that can't be parsed is an
+/// error, since it may hold what should be removed.
+fn strip_xmp(packet: &mut [u8], presets: &[StripPreset]) -> Result<(), ExifError> {
+    if !strips_xmp(presets) {
+        return Ok(());
+    }
+    let ranges = find_xmp_properties(packet, |namespace, name| {
+        is_xmp_stripped(presets, namespace, name)
+    })
+    .map_err(ExifError::SerializeError)?;
+    for range in ranges {
+        for b in &mut packet[range] {
+            *b = b' ';
+        }
+    }
+    Ok(())
+}
+
+/// Removes metadata in place from the extended XMP packet of a JPEG image, whose
+/// chunks are spread over APP1 segments. The packet is reassembled, stripped
+/// and written back over the chunks. Since its GUID is the MD5 digest of the
+/// packet, it is replaced, in the chunks and in the main packet, by the digest
+/// of the stripped packet.
+fn strip_extended_xmp(
+    jpeg: &mut [u8],
+    segments: &[(u16, Range<usize>)],
+    presets: &[StripPreset],
+) -> Result<(), ExifError> {
+    let main = segments
+        .iter()
+        .find(|(marker, range)| *marker == 0xffe1 && jpeg[range.clone()].starts_with(XMP_JPEG_NS))
+        .map(|(_, range)| range.start + XMP_JPEG_NS.len()..range.end);
+    let main = match main {
+        Some(main) if strips_xmp(presets) => main,
+        _ => return Ok(()),
+    };
+    // a main packet that can't be parsed has been reported by strip_xmp() already
+    let guid = match parse_xmp(&jpeg[main.clone()]) {
+        Ok(xmp) => match extended_xmp_guid(&xmp) {
+            Some(guid) => guid.to_string(),
+            None => return Ok(()),
+        },
+        Err(_) => return Ok(()),
+    };
+
+    let mut packet = find_extended_xmp_in_jpeg(jpeg, &guid).map_err(ExifError::SerializeError)?;
+    let original = packet.clone();
+    strip_xmp(&mut packet, presets)?;
+    if packet == original {
+        return Ok(());
+    }
+    let new_guid = format!("{:X}", md5::compute(&packet));
+
+    for (marker, range) in segments {
+        let data = &mut jpeg[range.clone()];
+        if *marker != 0xffe1 || !data.starts_with(XMP_EXTENSION_NS) {
+            continue;
+        }
+        let data = &mut data[XMP_EXTENSION_NS.len()..];
+        if data.len() < 40 || !data[0..32].eq_ignore_ascii_case(guid.as_bytes()) {
+            continue;
+        }
+        data[0..32].copy_from_slice(new_guid.as_bytes());
+        let offset = read_u32(false, &data[36..40]) as usize;
+        let chunk = &mut data[40..];
+        let end = offset.saturating_add(chunk.len()).min(packet.len());
+        if offset < end {
+            chunk[..end - offset].copy_from_slice(&packet[offset..end]);
+        }
+    }
+
+    let main = &mut jpeg[main];
+    if let Some(p) = main
+        .windows(guid.len())
+        .position(|w| w.eq_ignore_ascii_case(guid.as_bytes()))
+    {
+        main[p..p + guid.len()].copy_from_slice(new_guid.as_bytes());
+    }
+    Ok(())
+}
+
+/// Removes metadata in place from the EXIF data and the XMP packet stored in a
+/// Photoshop image resource block. Resources after a malformed one, e.g. one
+/// split across JPEG segments, are left alone.
+fn strip_photoshop(irb: &mut [u8], presets: &[StripPreset]) -> Result<(), ExifError> {
+    let resources: Vec<(u16, Range<usize>)> = irb_resources(irb)
+        .map_while(Result::ok)
+        .map(|r| (r.id, r.offset..r.offset + r.data.len()))
+        .collect();
+    for (id, range) in resources {
+        match id {
+            EXIF_RESOURCE => strip_tiff(&mut irb[range], presets)?,
+            XMP_RESOURCE => strip_xmp(&mut irb[range], presets)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Removes metadata in place from a TIFF block
+fn strip_tiff(tiff: &mut [u8], presets: &[StripPreset]) -> Result<(), ExifError> {
+    let (le, big, ifd0) = parse_tiff_header(tiff)?;
+
+    for preset in presets {
+        match *preset {
+            StripPreset::AllExif => {
+                remove_entries(tiff, le, big, ifd0, |tag| {
+                    is_sub_ifd(tag) || IFD0_METADATA_TAGS.contains(&tag)
+                })?;
+            }
+            StripPreset::Gps => {
+                remove_entries(tiff, le, big, ifd0, |tag| tag == tag_of(ExifTag::GPSOffset))?;
+            }
+            StripPreset::SerialNumbers => {
+                remove_entries(tiff, le, big, ifd0, |tag| IFD0_SERIAL_TAGS.contains(&tag))?;
+                if let Some(exif) = find_sub_ifd(tiff, le, big, ifd0, ExifTag::ExifOffset) {
+                    remove_entries(tiff, le, big, exif, |tag| EXIF_SERIAL_TAGS.contains(&tag))?;
+                    let maker_note = find_sub_ifd(tiff, le, big, exif, ExifTag::MakerNote);
+                    if let Some(maker_note) = maker_note.filter(|_| is_canon(tiff, le, big, ifd0)) {
+                        // the Canon MakerNote is a classic IFD, even in BigTIFF files
+                        remove_entries(tiff, le, false, maker_note, |tag| {
+                            CANON_SERIAL_TAGS.contains(&tag)
+                        })?;
+                    }
+                }
+            }
+            StripPreset::MakerNote => {
+                if let Some(exif) = find_sub_ifd(tiff, le, big, ifd0, ExifTag::ExifOffset) {
+                    remove_entries(tiff, le, big, exif, |tag| tag == tag_of(ExifTag::MakerNote))?;
+                }
+            }
+        }
+    }
+
+    // XMP packet of TIFF images
+    let xmp = read_ifd(tiff, le, big, ifd0)?
+        .into_iter()
+        .find(|e| e.tag == XMP_TAG && !e.in_ifd());
+    if let Some(entry) = xmp {
+        let offset = entry.data_as_offset();
+        let end = offset.saturating_add(entry.length()).min(tiff.len());
+        if offset < end {
+            strip_xmp(&mut tiff[offset..end], presets)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the selected metadata from a JPEG or TIFF image, and returns the
+/// rewritten image.
+///
+/// Entries are removed from their IFDs, and their data is overwritten with zeros,
+/// so no trace of them is left in the file. Nothing else is moved, so the image
+/// data and the remaining metadata are unchanged. The exception is
+/// `StripPreset::AllExif` on JPEG images, which drops the whole EXIF segment.
+///
+/// The XMP packets of JPEG images, main and extended, and the copy of the EXIF
+/// data that Photoshop stores in their APP13 segment are stripped as well, in
+/// place. So is the XMP packet of TIFF images.
+pub fn strip(contents: &[u8], presets: &[StripPreset]) -> Result<Vec<u8>, ExifError> {
+    match detect_type(contents) {
+        FileType::TIFF => {
+            let mut out = contents.to_vec();
+            strip_tiff(&mut out, presets)?;
+            Ok(out)
+        }
+        FileType::JPEG => {
+            let mut out = if presets.contains(&StripPreset::AllExif) {
+                replace_exif_in_jpeg(contents, None)?
+            } else {
+                contents.to_vec()
+            };
+            // duplicate EXIF segments are stripped too
+            let segments: Vec<(u16, Range<usize>)> = jpeg_segments(&out)
+                .map(|s| s.map(|s| (s.marker, s.offset + 4..s.end())))
+                .collect::<Result<_, _>>()?;
+            for (marker, range) in &segments {
+                let data = &mut out[range.clone()];
+                match *marker {
+                    0xffe1 if data.starts_with(b"Exif\0\0") => strip_tiff(&mut data[6..], presets)?,
+                    0xffe1 if data.starts_with(XMP_JPEG_NS) => {
+                        strip_xmp(&mut data[XMP_JPEG_NS.len()..], presets)?
+                    }
+                    0xffed if data.starts_with(PHOTOSHOP_JPEG_ID) => {
+                        strip_photoshop(&mut data[PHOTOSHOP_JPEG_ID.len()..], presets)?
+                    }
+                    _ => (),
+                }
+            }
+            strip_extended_xmp(&mut out, &segments, presets)?;
+            Ok(out)
+        }
+        _ => Err(ExifError::FileTypeUnknown),
+    }
+}
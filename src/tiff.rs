@@ -1,12 +1,84 @@
+use super::canon::*;
 use super::exif::*;
 use super::exifpost::*;
+use super::exifreadable::ucs2_text;
 use super::ifdformat::*;
 use super::lowlevel::*;
 use super::types::*;
 use super::types_impl::*;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
 
 type InExifResult = Result<(), ExifError>;
 
+/// Maximum number of IFDs followed in the chain that starts at IFD0
+const MAX_IFD_CHAIN: usize = 1024;
+
+/// Random access to the contents of a TIFF block, that may be in memory
+/// or read on demand from a file
+pub(crate) trait TiffSource {
+    /// Size of the TIFF block
+    fn size(&self) -> usize;
+
+    /// Reads `length` bytes at `offset`. Returns None if they are past the end.
+    fn read(&mut self, offset: usize, length: usize) -> Option<Vec<u8>>;
+}
+
+impl TiffSource for &[u8] {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn read(&mut self, offset: usize, length: usize) -> Option<Vec<u8>> {
+        self.get(offset..offset.checked_add(length)?)
+            .map(|data| data.to_vec())
+    }
+}
+
+/// TIFF block that is read on demand from a seekable stream, e.g. a TIFF file.
+/// An I/O error is kept, so that it can be told apart from truncated data.
+pub(crate) struct ReaderSource<R> {
+    reader: R,
+    size: usize,
+    pub error: Option<io::Error>,
+}
+
+impl<R: Read + Seek> ReaderSource<R> {
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let size = reader.seek(SeekFrom::End(0))? as usize;
+        Ok(ReaderSource {
+            reader,
+            size,
+            error: None,
+        })
+    }
+}
+
+impl<R: Read + Seek> TiffSource for ReaderSource<R> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn read(&mut self, offset: usize, length: usize) -> Option<Vec<u8>> {
+        // checked before allocating, the length comes from the file
+        if offset.checked_add(length)? > self.size {
+            return None;
+        }
+        let mut data = vec![0; length];
+        let res = self
+            .reader
+            .seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| self.reader.read_exact(&mut data));
+        match res {
+            Ok(_) => Some(data),
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
 /// Parse of raw IFD entry into EXIF data, if it is of a known type, and returns
 /// an ExifEntry object. If the tag is unknown, the enumeration is set to UnknownToMe,
 /// but the raw information of tag is still available in the ifd member.
@@ -15,6 +87,7 @@ pub fn parse_exif_entry(f: &IfdEntry, warnings: &mut Vec<String>) -> ExifEntry {
 
     let mut e = ExifEntry {
         namespace: f.namespace,
+        ifd_kind: f.ifd_kind,
         ifd: f.clone(),
         tag: ExifTag::UnknownToMe,
         value: value.clone(),
@@ -22,7 +95,7 @@ pub fn parse_exif_entry(f: &IfdEntry, warnings: &mut Vec<String>) -> ExifEntry {
         value_more_readable: format!("{}", value),
     };
 
-    let (tag, unit, format, min_count, max_count, more_readable) = tag_to_exif(f.tag);
+    let (tag, unit, format, min_count, max_count, more_readable) = tag_to_exif(f.namespace, f.tag);
 
     if tag == ExifTag::UnknownToMe {
         // Unknown EXIF tag type
@@ -30,10 +103,11 @@ pub fn parse_exif_entry(f: &IfdEntry, warnings: &mut Vec<String>) -> ExifEntry {
     }
 
     // Internal assert:
-    // 1) tag must match enum
+    // 1) tag and namespace must match enum
     // 2) all types except Ascii, Undefined, Unknown must have definite length
     // 3) Str type must not have a definite length
     if (((tag as u32) & 0xffff) as u16) != f.tag
+        || (((tag as u32) >> 16) as u16) != f.namespace as u16
         || (min_count == -1
             && (format != IfdFormat::Ascii
                 && format != IfdFormat::Undefined
@@ -43,7 +117,7 @@ pub fn parse_exif_entry(f: &IfdEntry, warnings: &mut Vec<String>) -> ExifEntry {
         panic!("Internal error {:x}", f.tag);
     }
 
-    if format != f.format {
+    if !format_matches(format, f.format) {
         warnings.push(format!(
             "EXIF tag {:x} {} ({}), expected format {} ({:?}), found {} ({:?})",
             f.tag, f.tag, tag, format as u8, format, f.format as u8, f.format
@@ -61,33 +135,116 @@ pub fn parse_exif_entry(f: &IfdEntry, warnings: &mut Vec<String>) -> ExifEntry {
 
     e.tag = tag;
     e.unit = unit.to_string();
+    if is_ucs2_tag(tag) {
+        if let TagValue::U8(ref v) = e.value {
+            // Inline values carry the padding of the IFD entry
+            let len = v.len().min(f.count as usize);
+            e.value = TagValue::Utf8(ucs2_text(&v[..len]));
+        }
+    }
     e.value_more_readable = more_readable(&e.value);
 
     e
 }
 
-/// Superficial parse of IFD that can't fail
+/// Text tags of Windows Explorer, that are stored as BYTE arrays of UCS-2 text
+/// and whose value is replaced by the decoded text
+fn is_ucs2_tag(tag: ExifTag) -> bool {
+    matches!(
+        tag,
+        ExifTag::XPTitle
+            | ExifTag::XPComment
+            | ExifTag::XPAuthor
+            | ExifTag::XPKeywords
+            | ExifTag::XPSubject
+    )
+}
+
+/// Checks the format of an IFD entry against the format expected for the tag.
+/// Tags expected to be LONG may also be SHORT, since the standard allows either
+/// for tags like RelatedImageWidth, IFD for offsets of IFDs, and LONG8 or IFD8
+/// in BigTIFF files. Their readable functions must handle all of them.
+///
+/// Likewise, text tags expected to be ASCII may be UTF-8 since Exif 3.0, and
+/// tags expected to be Unknown may be of any numeric format, like the BlackLevel
+/// tag of DNG that may be SHORT, LONG or RATIONAL.
+fn format_matches(expected: IfdFormat, found: IfdFormat) -> bool {
+    expected == found
+        || (expected == IfdFormat::Unknown && is_numeric(found))
+        || (expected == IfdFormat::Ascii && found == IfdFormat::Utf8)
+        || (expected == IfdFormat::U32
+            && matches!(
+                found,
+                IfdFormat::U16 | IfdFormat::Ifd | IfdFormat::U64 | IfdFormat::Ifd64
+            ))
+}
+
+fn is_numeric(format: IfdFormat) -> bool {
+    !matches!(
+        format,
+        IfdFormat::Ascii
+            | IfdFormat::Utf8
+            | IfdFormat::Undefined
+            | IfdFormat::Unknown
+            | IfdFormat::Ifd
+            | IfdFormat::Ifd64
+    )
+}
+
+/// Sizes of the entry count of an IFD, of each entry, and of offsets. They are
+/// 2, 12 and 4 bytes in classic TIFF, and 8, 20 and 8 bytes in BigTIFF.
+pub(crate) fn ifd_layout(big: bool) -> (usize, usize, usize) {
+    if big {
+        (8, 20, 8)
+    } else {
+        (2, 12, 4)
+    }
+}
+
+/// Reads the entry count of an IFD, or an offset, that are 64-bit in BigTIFF
+pub(crate) fn read_word(le: bool, raw: &[u8]) -> usize {
+    if raw.len() == 8 {
+        read_u64(le, raw) as usize
+    } else if raw.len() == 4 {
+        read_u32(le, raw) as usize
+    } else {
+        read_u16(le, raw) as usize
+    }
+}
+
+/// Superficial parse of IFD that can't fail. In BigTIFF (`big`), entries whose
+/// count does not fit in 32 bits are skipped, since their data could not be
+/// loaded anyway.
 pub fn parse_ifd(
     subifd: bool,
+    ifd_kind: IfdKind,
     le: bool,
-    count: u16,
+    big: bool,
+    count: usize,
     contents: &[u8],
 ) -> Option<(Vec<IfdEntry>, usize)> {
+    let (_, entry_size, offset_size) = ifd_layout(big);
     let mut entries: Vec<IfdEntry> = Vec::new();
 
     for i in 0..count {
-        let mut offset = (i as usize) * 12;
+        let mut offset = i * entry_size;
         let tag = read_u16(le, &contents.get(offset..offset + 2)?);
         offset += 2;
         let format = read_u16(le, &contents.get(offset..offset + 2)?);
         offset += 2;
-        let count = read_u32(le, &contents.get(offset..offset + 4)?);
-        offset += 4;
-        let data = &contents.get(offset..offset + 4)?;
+        let count = read_word(le, &contents.get(offset..offset + offset_size)?);
+        offset += offset_size;
+        let data = &contents.get(offset..offset + offset_size)?;
         let data = data.to_vec();
 
+        if count > u32::MAX as usize {
+            continue;
+        }
+        let count = count as u32;
+
         let entry = IfdEntry {
-            namespace: Namespace::Standard,
+            namespace: ifd_kind.namespace(),
+            ifd_kind,
             tag,
             format: ifdformat_new(format),
             count,
@@ -102,50 +259,65 @@ pub fn parse_ifd(
     let next_ifd = if subifd {
         0
     } else {
-        read_u32(le, &contents[count as usize * 12..]) as usize
+        let offset = count * entry_size;
+        read_word(le, contents.get(offset..offset + offset_size)?)
     };
 
     Some((entries, next_ifd))
 }
 
-/// Deep parse of IFD that grabs EXIF data from IFD0, SubIFD and GPS IFD
-fn parse_exif_ifd(
+/// Deep parse of IFD that grabs EXIF data from IFD0, SubIFD, GPS IFD, Interoperability IFD
+/// and the IFD chain
+fn parse_exif_ifd<S: TiffSource>(
+    ifd_kind: IfdKind,
     le: bool,
-    contents: &[u8],
+    big: bool,
+    contents: &mut S,
     ioffset: usize,
     exif_entries: &mut Vec<ExifEntry>,
     warnings: &mut Vec<String>,
 ) -> InExifResult {
+    let (count_size, entry_size, _) = ifd_layout(big);
     let mut offset = ioffset;
 
-    if contents.len() < (offset + 2) {
-        return Err(ExifError::ExifIfdTruncated(
-            "Truncated at dir entry count".to_string(),
-        ));
+    match offset.checked_add(count_size) {
+        Some(end) if contents.size() >= end => (),
+        _ => {
+            return Err(ExifError::ExifIfdTruncated(
+                "Truncated at dir entry count".to_string(),
+            ))
+        }
     }
 
-    let count = read_u16(
+    let count = read_word(
         le,
         &contents
-            .get(offset..offset + 2)
+            .read(offset, count_size)
             .ok_or(ExifError::IfdTruncated)?,
     );
-    let ifd_length = (count as usize) * 12;
-    offset += 2;
+    offset += count_size;
 
-    if contents.len() < (offset + ifd_length) {
-        return Err(ExifError::ExifIfdTruncated(
-            "Truncated at dir listing".to_string(),
-        ));
-    }
+    let ifd_length = match count.checked_mul(entry_size) {
+        Some(ifd_length) if contents.size() >= offset.saturating_add(ifd_length) => ifd_length,
+        _ => {
+            return Err(ExifError::ExifIfdTruncated(
+                "Truncated at dir listing".to_string(),
+            ))
+        }
+    };
 
     let ifd_content = &contents
-        .get(offset..offset + ifd_length)
+        .read(offset, ifd_length)
         .ok_or(ExifError::IfdTruncated)?;
-    let (mut ifd, _) = parse_ifd(true, le, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
+    let (mut ifd, _) =
+        parse_ifd(true, ifd_kind, le, big, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
+
+    if ifd_kind == IfdKind::Ifd1 {
+        copy_thumbnail(&mut ifd, contents);
+    }
 
     for entry in &mut ifd {
-        if !entry.copy_data(contents) {
+        if !entry.copy_data_from(contents) {
             // data is probably beyond EOF
             continue;
         }
@@ -153,14 +325,193 @@ fn parse_exif_ifd(
         exif_entries.push(exif_entry);
     }
 
+    if ifd_kind == IfdKind::Exif {
+        parse_interop_ifd(le, big, contents, &ifd, exif_entries, warnings);
+        parse_makernote(le, big, contents, &ifd, exif_entries, warnings);
+    }
+
     Ok(())
 }
 
-/// Parses IFD0 and looks for SubIFD or GPS IFD within IFD0
-pub fn parse_ifds(
+/// Parses the Interoperability IFD pointed by the Exif SubIFD, if any. Problems
+/// are reported as warnings, since the rest of the EXIF data is still good.
+fn parse_interop_ifd<S: TiffSource>(
+    le: bool,
+    big: bool,
+    contents: &mut S,
+    exif_ifd: &[IfdEntry],
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) {
+    let interop_tag = ((ExifTag::InteroperabilityOffset as u32) & 0xffff) as u16;
+
+    if let Some(entry) = exif_ifd.iter().find(|e| e.tag == interop_tag) {
+        let offset = entry.data_as_offset();
+        if let Err(e) = parse_exif_ifd(
+            IfdKind::Interop,
+            le,
+            big,
+            contents,
+            offset,
+            exif_entries,
+            warnings,
+        ) {
+            warnings.push(format!(
+                "Interoperability IFD at offset {} ignored: {}",
+                offset, e
+            ));
+        }
+    }
+}
+
+/// Parses the MakerNote of the Exif SubIFD, if the manufacturer is known to store
+/// it as an IFD. So far, only Canon MakerNotes are decoded.
+fn parse_makernote<S: TiffSource>(
     le: bool,
+    big: bool,
+    contents: &mut S,
+    exif_ifd: &[IfdEntry],
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) {
+    let makernote_tag = ((ExifTag::MakerNote as u32) & 0xffff) as u16;
+
+    if let Some(entry) = exif_ifd.iter().find(|e| e.tag == makernote_tag) {
+        if !big && !entry.in_ifd() && is_canon(exif_entries) {
+            let offset = entry.data_as_offset();
+            let length = entry.count as usize;
+            parse_canon_makernote(le, contents, offset, length, exif_entries, warnings);
+        }
+    }
+}
+
+/// Parses the IFDs pointed by the SubIFDs tag of IFD0, that hold e.g. the raw
+/// image of a DNG file. Problems are reported as warnings, like for the IFD chain.
+fn parse_sub_ifds<S: TiffSource>(
+    le: bool,
+    big: bool,
+    contents: &mut S,
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) {
+    let sub_ifds_tag = ((ExifTag::SubIFDs as u32) & 0xffff) as u16;
+
+    let offsets: Vec<usize> = match exif_entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == IfdKind::Ifd0 && e.ifd.tag == sub_ifds_tag)
+    {
+        Some(entry) => (0..MAX_IFD_CHAIN)
+            .map_while(|i| entry.value.to_i64(i))
+            .map(|offset| offset as usize)
+            .collect(),
+        None => return,
+    };
+
+    for (i, offset) in offsets.into_iter().enumerate() {
+        let ifd_kind = IfdKind::SubIfd(i as u16);
+        if let Err(e) = parse_exif_ifd(ifd_kind, le, big, contents, offset, exif_entries, warnings)
+        {
+            warnings.push(format!(
+                "{:?} at offset {} ignored: {}",
+                ifd_kind, offset, e
+            ));
+        }
+    }
+}
+
+/// Copies the JPEG thumbnail pointed by JPEGInterchangeFormat
+/// and JPEGInterchangeFormatLength into the ext_data of the former.
+fn copy_thumbnail<S: TiffSource>(ifd: &mut [IfdEntry], contents: &mut S) {
+    let length_tag = ((ExifTag::JPEGInterchangeFormatLength as u32) & 0xffff) as u16;
+    let offset_tag = ((ExifTag::JPEGInterchangeFormat as u32) & 0xffff) as u16;
+
+    let length = ifd
+        .iter_mut()
+        .find(|e| e.tag == length_tag && e.in_ifd())
+        .and_then(|e| {
+            e.copy_data_from(contents);
+            tag_value_new(e).to_i64(0)
+        });
+
+    if let (Some(length), Some(entry)) = (length, ifd.iter_mut().find(|e| e.tag == offset_tag)) {
+        if !entry.in_ifd() || !entry.copy_data_from(contents) {
+            return;
+        }
+        let offset = match tag_value_new(entry).to_i64(0) {
+            Some(offset) => offset as usize,
+            None => return,
+        };
+        if let Some(thumbnail) = contents.read(offset, length as usize) {
+            entry.ext_data = thumbnail;
+        }
+    }
+}
+
+/// Parses the IFDs that follow IFD0 in the chain, starting with IFD1.
+///
+/// Problems are reported as warnings, not errors, because many images carry
+/// a broken chain, and losing IFD0 data due to it would be unfortunate.
+fn parse_ifd_chain<S: TiffSource>(
+    le: bool,
+    big: bool,
+    contents: &mut S,
     ifd0_offset: usize,
-    contents: &[u8],
+    next_ifd: usize,
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) {
+    let mut visited = vec![ifd0_offset];
+    let mut offset = next_ifd;
+
+    while offset != 0 {
+        if visited.contains(&offset) {
+            warnings.push(format!("IFD chain loops back to offset {}", offset));
+            return;
+        }
+        if visited.len() > MAX_IFD_CHAIN {
+            warnings.push(format!("IFD chain longer than {} IFDs", MAX_IFD_CHAIN));
+            return;
+        }
+
+        let ifd_kind = if visited.len() == 1 {
+            IfdKind::Ifd1
+        } else {
+            IfdKind::Page(visited.len() as u16)
+        };
+        visited.push(offset);
+
+        if let Err(e) = parse_exif_ifd(ifd_kind, le, big, contents, offset, exif_entries, warnings)
+        {
+            warnings.push(format!(
+                "{:?} at offset {} ignored: {}",
+                ifd_kind, offset, e
+            ));
+            return;
+        }
+
+        // the next IFD offset follows the entries
+        let (count_size, entry_size, offset_size) = ifd_layout(big);
+        let count = match contents.read(offset, count_size) {
+            Some(count) => read_word(le, &count),
+            None => return,
+        };
+        let next_offset = count
+            .checked_mul(entry_size)
+            .and_then(|ifd_length| offset.checked_add(count_size)?.checked_add(ifd_length));
+        offset = match next_offset.and_then(|next_offset| contents.read(next_offset, offset_size)) {
+            Some(next) => read_word(le, &next),
+            None => 0,
+        };
+    }
+}
+
+/// Parses IFD0, looks for SubIFD or GPS IFD within IFD0,
+/// then follows the chain of IFDs after IFD0
+pub(crate) fn parse_ifds<S: TiffSource>(
+    le: bool,
+    big: bool,
+    ifd0_offset: usize,
+    contents: &mut S,
     warnings: &mut Vec<String>,
 ) -> ExifEntryResult {
     let mut offset = ifd0_offset;
@@ -168,7 +519,15 @@ pub fn parse_ifds(
 
     // fills exif_entries with data from IFD0
 
-    match parse_exif_ifd(le, contents, offset, &mut exif_entries, warnings) {
+    match parse_exif_ifd(
+        IfdKind::Ifd0,
+        le,
+        big,
+        contents,
+        offset,
+        &mut exif_entries,
+        warnings,
+    ) {
         Ok(_) => true,
         Err(e) => return Err(e),
     };
@@ -176,41 +535,65 @@ pub fn parse_ifds(
     // at this point we knot that IFD0 is good
     // looks for SubIFD (EXIF)
 
-    let count = read_u16(
+    let (count_size, entry_size, offset_size) = ifd_layout(big);
+    let count = read_word(
         le,
         &contents
-            .get(offset..offset + 2)
+            .read(offset, count_size)
             .ok_or(ExifError::IfdTruncated)?,
     );
-    let ifd_length = (count as usize) * 12 + 4;
-    offset += 2;
+    let ifd_length = count * entry_size + offset_size;
+    offset += count_size;
 
     let ifd_content = &contents
-        .get(offset..offset + ifd_length)
+        .read(offset, ifd_length)
+        .ok_or(ExifError::IfdTruncated)?;
+    let (ifd, next_ifd) = parse_ifd(false, IfdKind::Ifd0, le, big, count, ifd_content)
         .ok_or(ExifError::IfdTruncated)?;
-    let (ifd, _) = parse_ifd(false, le, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
 
     for entry in &ifd {
-        if entry.tag != (((ExifTag::ExifOffset as u32) & 0xffff) as u16)
-            && entry.tag != (((ExifTag::GPSOffset as u32) & 0xffff) as u16)
-        {
+        let ifd_kind = if entry.tag == (((ExifTag::ExifOffset as u32) & 0xffff) as u16) {
+            IfdKind::Exif
+        } else if entry.tag == (((ExifTag::GPSOffset as u32) & 0xffff) as u16) {
+            IfdKind::Gps
+        } else {
             continue;
-        }
+        };
 
         let exif_offset = entry.data_as_offset();
 
-        if contents.len() < exif_offset {
+        if contents.size() < exif_offset {
             return Err(ExifError::ExifIfdTruncated(
                 "Exif SubIFD goes past EOF".to_string(),
             ));
         }
 
-        match parse_exif_ifd(le, contents, exif_offset, &mut exif_entries, warnings) {
+        match parse_exif_ifd(
+            ifd_kind,
+            le,
+            big,
+            contents,
+            exif_offset,
+            &mut exif_entries,
+            warnings,
+        ) {
             Ok(_) => true,
             Err(e) => return Err(e),
         };
     }
 
+    parse_sub_ifds(le, big, contents, &mut exif_entries, warnings);
+
+    parse_ifd_chain(
+        le,
+        big,
+        contents,
+        ifd0_offset,
+        next_ifd,
+        &mut exif_entries,
+        warnings,
+    );
+
     // I didn't want to make the copy, but how to pass a vector that is
     // being iterated onto?
     let exif_entries_copy = exif_entries.clone();
@@ -222,17 +605,19 @@ pub fn parse_ifds(
     Ok(exif_entries)
 }
 
-/// Parse a TIFF image, or embedded TIFF in JPEG, in order to get IFDs and then the EXIF data
-pub fn parse_tiff(contents: &[u8], warnings: &mut Vec<String>) -> ExifEntryResult {
-    let mut le = false;
+/// Parse the TIFF header, returning the endianess (true=LE), whether the file is
+/// a BigTIFF, and the offset of IFD0
+pub fn parse_tiff_header(contents: &[u8]) -> Result<(bool, bool, usize), ExifError> {
+    let le;
 
     if contents.len() < 8 {
         return Err(ExifError::TiffTruncated);
-    } else if contents[0] == b'I' && contents[1] == b'I' && contents[2] == 42 && contents[3] == 0 {
+    } else if contents[0] == b'I' && contents[1] == b'I' {
         /* TIFF little-endian */
         le = true;
-    } else if contents[0] == b'M' && contents[1] == b'M' && contents[2] == 0 && contents[3] == 42 {
+    } else if contents[0] == b'M' && contents[1] == b'M' {
         /* TIFF big-endian */
+        le = false;
     } else {
         let err = format!(
             "Preamble is {:x} {:x} {:x} {:x}",
@@ -241,7 +626,59 @@ pub fn parse_tiff(contents: &[u8], warnings: &mut Vec<String>) -> ExifEntryResul
         return Err(ExifError::TiffBadPreamble(err));
     }
 
-    let offset = read_u32(le, &contents[4..8]) as usize;
+    match read_u16(le, &contents[2..4]) {
+        42 => Ok((le, false, read_u32(le, &contents[4..8]) as usize)),
+        43 => {
+            // BigTIFF: size of offsets (always 8) and a reserved word, then the offset
+            if contents.len() < 16 {
+                return Err(ExifError::TiffTruncated);
+            }
+            let offset_size = read_u16(le, &contents[4..6]);
+            if offset_size != 8 {
+                return Err(ExifError::TiffBadPreamble(format!(
+                    "BigTIFF with {}-byte offsets",
+                    offset_size
+                )));
+            }
+            Ok((le, true, read_u64(le, &contents[8..16]) as usize))
+        }
+        version => Err(ExifError::TiffBadPreamble(format!(
+            "Preamble is {:x} {:x}, version {}",
+            contents[0], contents[1], version
+        ))),
+    }
+}
+
+/// Parse a TIFF image, or embedded TIFF in JPEG, in order to get IFDs and then the EXIF data
+///
+/// Both classic TIFF and BigTIFF, with 64-bit offsets, are accepted.
+pub fn parse_tiff(contents: &[u8], warnings: &mut Vec<String>) -> ExifEntryResult {
+    let (le, big, offset) = parse_tiff_header(contents)?;
+    let mut contents = contents;
+
+    parse_ifds(le, big, offset, &mut contents, warnings)
+}
+
+/// Parse a TIFF image read on demand from a seekable stream. Only the IFDs
+/// and the values they point to are read.
+pub(crate) fn parse_tiff_reader<R: Read + Seek>(
+    reader: R,
+    warnings: &mut Vec<String>,
+) -> ExifEntryResult {
+    let mut source = ReaderSource::new(reader)?;
+    // long enough for the header of BigTIFF
+    let header_length = source.size().min(16);
+    let header = source
+        .read(0, header_length)
+        .ok_or(ExifError::TiffTruncated);
+    if let Some(e) = source.error.take() {
+        return Err(ExifError::IoError(e));
+    }
+    let (le, big, offset) = parse_tiff_header(&header?)?;
 
-    parse_ifds(le, offset, &contents, warnings)
+    let res = parse_ifds(le, big, offset, &mut source, warnings);
+    match source.error {
+        Some(e) => Err(ExifError::IoError(e)),
+        None => res,
+    }
 }
@@ -6,10 +6,349 @@ use std::result::Result;
 /// Top-level structure that contains all parsed metadata inside an image
 #[derive(Debug)]
 pub struct ExifData {
-    /// MIME type of the parsed image. It may be "image/jpeg", "image/tiff", or empty if unrecognized.
+    /// MIME type of the parsed image. It may be "image/jpeg", "image/tiff", "image/png",
+    /// "image/webp", "image/heic", "image/avif", "image/jxl", "image/vnd.adobe.photoshop",
+    /// or empty if unrecognized.
     pub mime: String,
-    /// Collection of EXIF entries found in the image
+    /// Collection of EXIF entries found in the image. It is empty for images
+    /// that have other metadata, e.g. XMP, but no EXIF data.
     pub entries: Vec<ExifEntry>,
+    /// XMP packet found in the image, if any. The properties of the extended
+    /// packet of a JPEG image are merged into it.
+    pub xmp: Option<XmpData>,
+    /// IPTC-IIM datasets found in the image, if any
+    pub iptc: Option<IptcData>,
+    /// Photoshop image resources found in the image, if any
+    pub photoshop: Option<PhotoshopData>,
+    /// ICC color profile embedded in the image, if any
+    pub icc: Option<IccProfile>,
+    /// Multi-Picture Format index of a JPEG image that holds further images,
+    /// e.g. a stereo pair, a depth map or an HDR gain map
+    pub mpf: Option<MpfData>,
+}
+
+/// IPTC-IIM metadata, as written by news agencies and photo editors
+#[derive(Clone, Debug)]
+pub struct IptcData {
+    /// Datasets in the order they were found. Repeatable datasets, like
+    /// keywords, appear once for each value.
+    pub datasets: Vec<IptcDataset>,
+}
+
+/// Dataset of an IPTC-IIM stream
+#[derive(Clone, Debug)]
+pub struct IptcDataset {
+    /// Record number, e.g. 2 for the application record
+    pub record: u8,
+    /// Dataset number within the record
+    pub dataset: u8,
+    /// Dataset as an enumeration, `Unknown` if not recognized
+    pub tag: IptcTag,
+    /// Raw data of the dataset
+    pub data: Vec<u8>,
+    /// Data decoded as text, in the character set given by the
+    /// CodedCharacterSet dataset. Numbers are formatted in decimal.
+    pub value: String,
+}
+
+/// Enumeration of the recognized IPTC-IIM datasets.
+///
+/// Items can be cast to u16 to get the record number (most significant byte)
+/// and dataset number (least significant byte).
+#[derive(Copy, Clone, Debug, PartialEq, Hash)]
+pub enum IptcTag {
+    Unknown = 0xffff,
+    CodedCharacterSet = 0x015a,
+    RecordVersion = 0x0200,
+    ObjectName = 0x0205,
+    Urgency = 0x020a,
+    Category = 0x020f,
+    SupplementalCategories = 0x0214,
+    Keywords = 0x0219,
+    SpecialInstructions = 0x0228,
+    DateCreated = 0x0237,
+    TimeCreated = 0x023c,
+    Byline = 0x0250,
+    BylineTitle = 0x0255,
+    City = 0x025a,
+    SubLocation = 0x025c,
+    ProvinceState = 0x025f,
+    CountryCode = 0x0264,
+    Country = 0x0265,
+    OriginalTransmissionReference = 0x0267,
+    Headline = 0x0269,
+    Credit = 0x026e,
+    Source = 0x0273,
+    CopyrightNotice = 0x0274,
+    Contact = 0x0276,
+    Caption = 0x0278,
+    WriterEditor = 0x027a,
+}
+
+impl fmt::Display for IptcTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                IptcTag::Unknown => "Unknown to this library",
+                IptcTag::CodedCharacterSet => "Coded character set",
+                IptcTag::RecordVersion => "Record version",
+                IptcTag::ObjectName => "Object name",
+                IptcTag::Urgency => "Urgency",
+                IptcTag::Category => "Category",
+                IptcTag::SupplementalCategories => "Supplemental categories",
+                IptcTag::Keywords => "Keywords",
+                IptcTag::SpecialInstructions => "Special instructions",
+                IptcTag::DateCreated => "Date created",
+                IptcTag::TimeCreated => "Time created",
+                IptcTag::Byline => "By-line",
+                IptcTag::BylineTitle => "By-line title",
+                IptcTag::City => "City",
+                IptcTag::SubLocation => "Sub-location",
+                IptcTag::ProvinceState => "Province/State",
+                IptcTag::CountryCode => "Country code",
+                IptcTag::Country => "Country",
+                IptcTag::OriginalTransmissionReference => "Original transmission reference",
+                IptcTag::Headline => "Headline",
+                IptcTag::Credit => "Credit",
+                IptcTag::Source => "Source",
+                IptcTag::CopyrightNotice => "Copyright notice",
+                IptcTag::Contact => "Contact",
+                IptcTag::Caption => "Caption/Abstract",
+                IptcTag::WriterEditor => "Writer/Editor",
+            }
+        )
+    }
+}
+
+/// XMP metadata, parsed from the RDF/XML packet embedded in the image
+#[derive(Clone, Debug)]
+pub struct XmpData {
+    /// The packet as found in the image
+    pub packet: String,
+    /// Top-level properties, of every rdf:Description of the packet
+    pub properties: Vec<XmpProperty>,
+}
+
+/// Property of an XMP packet, or field of an XMP structure
+#[derive(Clone, Debug)]
+pub struct XmpProperty {
+    /// Namespace URI, e.g. "http://purl.org/dc/elements/1.1/"
+    pub namespace: String,
+    /// Prefix bound to the namespace in the packet, e.g. "dc"
+    pub prefix: String,
+    /// Name of the property within the namespace, e.g. "subject"
+    pub name: String,
+    /// Language of the value, from the xml:lang qualifier
+    pub lang: Option<String>,
+    pub value: XmpValue,
+}
+
+/// Value of an XMP property
+#[derive(Clone, Debug)]
+pub enum XmpValue {
+    /// Text, number, date or URI, kept as found in the packet
+    Simple(String),
+    /// Structure, made of named fields
+    Struct(Vec<XmpProperty>),
+    /// Unordered (rdf:Bag), ordered (rdf:Seq) or alternative (rdf:Alt) array.
+    /// Language alternatives, e.g. dc:title, are alternative arrays whose items
+    /// have a language.
+    Array(XmpArrayKind, Vec<XmpItem>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum XmpArrayKind {
+    Bag,
+    Seq,
+    Alt,
+}
+
+/// Item of an XMP array
+#[derive(Clone, Debug)]
+pub struct XmpItem {
+    /// Language of the item, from the xml:lang qualifier
+    pub lang: Option<String>,
+    pub value: XmpValue,
+}
+
+/// ICC color profile, as raw data and a summary of its header and main tags.
+///
+/// Signatures are given as text, without padding spaces.
+#[derive(Clone, Debug)]
+pub struct IccProfile {
+    /// The whole profile, e.g. to be handed over to a color management system
+    pub data: Vec<u8>,
+    pub major_version: u8,
+    pub minor_version: u8,
+    /// Profile class, e.g. "mntr" for displays, "prtr" for printers,
+    /// "spac" for color spaces
+    pub class: String,
+    /// Color space of the image data, e.g. "RGB", "GRAY" or "CMYK"
+    pub color_space: String,
+    /// Profile connection space, "XYZ" or "Lab"
+    pub connection_space: String,
+    /// 0 for perceptual, 1 for media-relative colorimetric, 2 for saturation,
+    /// 3 for ICC-absolute colorimetric
+    pub rendering_intent: u32,
+    /// Description of the profile, e.g. "sRGB IEC61966-2.1" or "Display P3"
+    pub description: Option<String>,
+    pub copyright: Option<String>,
+    /// Media white point, in the XYZ color space
+    pub white_point: Option<[f64; 3]>,
+}
+
+/// Photoshop image resources, found in JPEG images saved by Photoshop, in TIFF
+/// images and in PSD files
+#[derive(Clone, Debug)]
+pub struct PhotoshopData {
+    /// Resources in the order they were found
+    pub resources: Vec<PhotoshopResource>,
+}
+
+/// Resource of a Photoshop image resource block
+#[derive(Clone, Debug)]
+pub struct PhotoshopResource {
+    /// Resource identifier, e.g. 1005 for resolution info
+    pub id: u16,
+    /// Name of the resource, usually empty
+    pub name: String,
+    /// Raw data of the resource
+    pub data: Vec<u8>,
+    /// Decoded data, `Raw` if the resource is not decoded
+    pub value: PhotoshopValue,
+}
+
+/// Decoded data of a Photoshop resource. IPTC-IIM (1028), EXIF (1058) and
+/// XMP (1060) resources are `Raw`, their contents are parsed into
+/// `ExifData::iptc`, `ExifData::entries` and `ExifData::xmp` instead.
+#[derive(Clone, Debug)]
+pub enum PhotoshopValue {
+    /// Resolution info (1005)
+    Resolution(PhotoshopResolution),
+    /// Thumbnail (1033 and 1036)
+    Thumbnail(PhotoshopThumbnail),
+    /// Copyright flag (1034), true if the image is copyrighted
+    CopyrightFlag(bool),
+    /// URL (1035)
+    Url(String),
+    /// Slices (1050)
+    Slices(PhotoshopSlices),
+    Raw,
+}
+
+#[derive(Clone, Debug)]
+pub struct PhotoshopResolution {
+    /// Horizontal resolution, in pixels per inch or per centimeter
+    pub x: f64,
+    /// Unit of the horizontal resolution: 1 for inches, 2 for centimeters
+    pub x_unit: u16,
+    /// Vertical resolution, in pixels per inch or per centimeter
+    pub y: f64,
+    /// Unit of the vertical resolution: 1 for inches, 2 for centimeters
+    pub y_unit: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct PhotoshopThumbnail {
+    /// 1 for JFIF data, 0 for raw pixels
+    pub format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_pixel: u16,
+    /// True for the thumbnail of Photoshop 4 (1033), whose JFIF data has the
+    /// red and blue channels swapped
+    pub bgr: bool,
+    /// Thumbnail data, usually JFIF
+    pub data: Vec<u8>,
+}
+
+/// Summary of the slices of an image, as cut for the web
+#[derive(Clone, Debug)]
+pub struct PhotoshopSlices {
+    /// Bounds of the group of slices
+    pub top: i32,
+    pub left: i32,
+    pub bottom: i32,
+    pub right: i32,
+    /// Name of the group of slices
+    pub group_name: String,
+    /// Number of slices
+    pub count: u32,
+}
+
+/// Multi-Picture Format (CIPA DC-007) data, found in the APP2 segment of the
+/// first image of a JPEG file. Further images are appended to the first one.
+#[derive(Clone, Debug)]
+pub struct MpfData {
+    /// Entries of the MP Index IFD, e.g. the version and the number of images
+    pub entries: Vec<ExifEntry>,
+    /// Images listed in the MP Entry tag, the first one being the primary image
+    pub images: Vec<MpImage>,
+}
+
+/// Image listed in the MP Entry tag of the MP Index IFD
+#[derive(Clone, Debug)]
+pub struct MpImage {
+    /// Individual image attribute: flags in the most significant bits, image
+    /// data format and image type. See `image_type()` and friends.
+    pub attribute: u32,
+    /// Size of the image, in bytes
+    pub size: u32,
+    /// Offset of the image within the file. It is 0 for the primary image.
+    pub offset: usize,
+    /// Indexes (starting at 1) of the dependent images, or 0
+    pub dependent_images: [u16; 2],
+    /// Entries of the MP Attribute IFD of the image, if found. For secondary
+    /// images, the IFD is stored in the APP2 segment of the image itself.
+    pub attributes: Vec<ExifEntry>,
+}
+
+/// Typed view of the tags of a DNG file, that describe how to turn the raw
+/// image data into colors. See `ExifData::dng()`.
+///
+/// Color tags come from IFD0, and the tags of the raw image data from the IFD
+/// that holds it, usually a sub-IFD, since IFD0 holds a preview.
+#[derive(Clone, Debug)]
+pub struct DngInfo {
+    /// DNGVersion, e.g. [1, 4, 0, 0]
+    pub version: [u8; 4],
+    /// Oldest version of the specification that readers must support
+    pub backward_version: Option<[u8; 4]>,
+    /// Name of the camera model, unique among manufacturers
+    pub unique_camera_model: Option<String>,
+    /// Name of the embedded camera profile
+    pub profile_name: Option<String>,
+    /// Matrices from the XYZ color space to the reference camera color space,
+    /// under the first, second and third calibration illuminants. Each one
+    /// has 3 columns and a row per color plane, in row-major order.
+    pub color_matrix1: Option<Vec<f64>>,
+    pub color_matrix2: Option<Vec<f64>>,
+    pub color_matrix3: Option<Vec<f64>>,
+    /// Light sources of the color matrices, as in `ExifTag::LightSource`
+    pub calibration_illuminant1: Option<u16>,
+    pub calibration_illuminant2: Option<u16>,
+    pub calibration_illuminant3: Option<u16>,
+    /// White balance at the time of capture, as the camera neutral color
+    pub as_shot_neutral: Option<Vec<f64>>,
+    /// Exposure compensation to apply to the raw image, in EV
+    pub baseline_exposure: Option<f64>,
+    /// IFD that holds the raw image data
+    pub raw_ifd: IfdKind,
+    /// Area of the sensor that holds image data: top, left, bottom and right
+    pub active_area: Option<[u32; 4]>,
+    /// Origin of the final image within the active area, horizontal first
+    pub default_crop_origin: Option<[f64; 2]>,
+    /// Size of the final image, width first
+    pub default_crop_size: Option<[f64; 2]>,
+    /// Zero light encoding level, per color plane and position within the
+    /// BlackLevelRepeatDim pattern
+    pub black_level: Option<Vec<f64>>,
+    /// Fully saturated encoding level, per color plane
+    pub white_level: Option<Vec<u32>>,
+    /// Lookup table that maps stored values to linear values
+    pub linearization_table: Option<Vec<u16>>,
 }
 
 /// Possible fatal errors that may happen when an image is parsed.
@@ -18,11 +357,17 @@ pub enum ExifError {
     IoError(io::Error),
     FileTypeUnknown,
     JpegWithoutExif(String),
+    PngWithoutExif(String),
+    WebpWithoutExif(String),
+    HeifWithoutExif(String),
+    JxlWithoutExif(String),
+    PsdWithoutExif(String),
     TiffTruncated,
     TiffBadPreamble(String),
     IfdTruncated,
     ExifIfdTruncated(String),
     ExifIfdEntryNotFound,
+    SerializeError(String),
 }
 
 /// Structure that represents a parsed IFD entry of a TIFF image
@@ -32,6 +377,9 @@ pub struct IfdEntry {
     /// other namespaces are entries found e.g. within MarkerNote blobs that are
     /// manufacturer-specific.
     pub namespace: Namespace,
+    /// IFD that contains the entry. When serializing, the entry is written
+    /// back to the IFD of the same kind.
+    pub ifd_kind: IfdKind,
     /// IFD tag value, may or not be an EXIF tag
     pub tag: u16,
     /// IFD data format
@@ -41,11 +389,15 @@ pub struct IfdEntry {
     /// Raw data as a vector of bytes. Length is sizeof(format) * count.
     /// Depending on its size, it came from different parts of the image file.
     pub data: Vec<u8>,
-    /// Raw data contained within the IFD structure. If count * sizeof(format) >= 4,
-    /// this item contains the offset where the actual data can be found
+    /// Raw data contained within the IFD structure, 4 bytes long, or 8 bytes long in
+    /// BigTIFF. If count * sizeof(format) exceeds that, this item contains the offset
+    /// where the actual data can be found
     pub ifd_data: Vec<u8>,
     /// Raw data contained outside of the IFD structure and pointed by ifd_data,
-    /// if data would not fit within the IFD structure
+    /// if data would not fit within the IFD structure.
+    ///
+    /// JPEGInterchangeFormat entries of IFD1 are offsets themselves, so this item
+    /// contains the thumbnail they point to.
     pub ext_data: Vec<u8>,
     /// If true, integer and offset formats must be parsed from raw data as little-endian.
     /// If false, integer and offset formats must be parsed from raw data as big-endian.
@@ -55,14 +407,51 @@ pub struct IfdEntry {
     pub le: bool,
 }
 
-/// Enumeration that represent EXIF tag namespaces. Namespaces exist to
-/// accomodate future parsing of the manufacturer-specific tags embedded within
-/// the MarkerNote tag.
+/// Enumeration that represent EXIF tag namespaces. A tag number has a different
+/// meaning in each namespace.
+///
+/// Standard namespace contains the TIFF and Exif tags found in IFD0, Exif SubIFD
+/// and IFD1. GPS, Interoperability and Multi-Picture Format IFDs have namespaces
+/// of their own. The manufacturer-specific namespaces accomodate tags embedded
+/// within the MarkerNote tag.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Namespace {
     Standard = 0x0000,
     Nikon = 0x0001,
     Canon = 0x0002,
+    Gps = 0x0003,
+    Interop = 0x0004,
+    Mpf = 0x0005,
+}
+
+/// Enumeration that represents the IFDs of a TIFF/EXIF block that the parser
+/// visits, and that the serializer writes. The IFD determines the `Namespace`
+/// of the tags found in it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IfdKind {
+    /// Main image IFD, the first one in the TIFF header
+    Ifd0,
+    /// Exif SubIFD, pointed by `ExifTag::ExifOffset` in IFD0
+    Exif,
+    /// GPS IFD, pointed by `ExifTag::GPSOffset` in IFD0
+    Gps,
+    /// IFD that follows IFD0 in the chain. In EXIF data, it describes the thumbnail.
+    Ifd1,
+    /// IFDs that follow IFD1 in the chain, e.g. further pages of a multi-page TIFF.
+    /// Contains the position in the chain, starting at 2.
+    Page(u16),
+    /// IFDs pointed by `ExifTag::SubIFDs` in IFD0, e.g. the raw image of a DNG file.
+    /// Contains the position in the tag, starting at 0.
+    SubIfd(u16),
+    /// Interoperability IFD, pointed by InteroperabilityOffset in the Exif SubIFD
+    Interop,
+    /// IFD embedded within the MakerNote tag. Its namespace depends on the manufacturer.
+    MakerNote,
+    /// MP Index IFD, the first IFD of the Multi-Picture Format segment
+    MpIndex,
+    /// MP Attribute IFD, that describes one of the images of a Multi-Picture
+    /// Format file
+    MpAttributes,
 }
 
 /// Enumeration that represents recognized EXIF tags found in TIFF IFDs.
@@ -72,31 +461,63 @@ pub enum Namespace {
 /// Makernote standard, depending on the namespace that the tag belongs to.
 ///
 /// On the other hand, the namespace code is arbitrary, it only matches
-/// the `Namespace` enumeration. The namespace is 0 for standard Exif tags.
-/// The non-standard namespaces exist to accomodate future parsing of the
-/// MarkerNote tag, that contains embedded manufacturer-specific tags.
+/// the `Namespace` enumeration. The namespace is 0 for standard Exif tags,
+/// and e.g. 3 for tags of the GPS IFD. The manufacturer-specific namespaces
+/// exist to accomodate parsing of the MarkerNote tag.
 #[derive(Copy, Clone, Debug, PartialEq, Hash)]
 pub enum ExifTag {
     /// Tag not recognized are partially parsed. The client may still try to interpret
     /// the tag by reading into the IfdFormat structure.
     UnknownToMe = 0x0000_ffff,
+    NewSubfileType = 0x0000_00fe,
+    ImageWidth = 0x0000_0100,
+    ImageLength = 0x0000_0101,
+    BitsPerSample = 0x0000_0102,
+    Compression = 0x0000_0103,
+    PhotometricInterpretation = 0x0000_0106,
+    DocumentName = 0x0000_010d,
     ImageDescription = 0x0000_010e,
     Make = 0x0000_010f,
     Model = 0x0000_0110,
+    StripOffsets = 0x0000_0111,
     Orientation = 0x0000_0112,
+    SamplesPerPixel = 0x0000_0115,
+    RowsPerStrip = 0x0000_0116,
+    StripByteCounts = 0x0000_0117,
     XResolution = 0x0000_011a,
     YResolution = 0x0000_011b,
+    PlanarConfiguration = 0x0000_011c,
     ResolutionUnit = 0x0000_0128,
     Software = 0x0000_0131,
     DateTime = 0x0000_0132,
+    Artist = 0x0000_013b,
     HostComputer = 0x0000_013c,
+    Predictor = 0x0000_013d,
     WhitePoint = 0x0000_013e,
     PrimaryChromaticities = 0x0000_013f,
+    TileWidth = 0x0000_0142,
+    TileLength = 0x0000_0143,
+    TileOffsets = 0x0000_0144,
+    TileByteCounts = 0x0000_0145,
+    SubIFDs = 0x0000_014a,
+    ExtraSamples = 0x0000_0152,
+    SampleFormat = 0x0000_0153,
     YCbCrCoefficients = 0x0000_0211,
+    YCbCrSubSampling = 0x0000_0212,
+    YCbCrPositioning = 0x0000_0213,
+    JPEGInterchangeFormat = 0x0000_0201,
+    JPEGInterchangeFormatLength = 0x0000_0202,
     ReferenceBlackWhite = 0x0000_0214,
+    Rating = 0x0000_4746,
+    RatingPercent = 0x0000_4749,
     Copyright = 0x0000_8298,
     ExifOffset = 0x0000_8769,
     GPSOffset = 0x0000_8825,
+    XPTitle = 0x0000_9c9b,
+    XPComment = 0x0000_9c9c,
+    XPAuthor = 0x0000_9c9d,
+    XPKeywords = 0x0000_9c9e,
+    XPSubject = 0x0000_9c9f,
 
     ExposureTime = 0x0000_829a,
     FNumber = 0x0000_829d,
@@ -105,9 +526,19 @@ pub enum ExifTag {
     ISOSpeedRatings = 0x0000_8827,
     OECF = 0x0000_8828,
     SensitivityType = 0x0000_8830,
+    StandardOutputSensitivity = 0x0000_8831,
+    RecommendedExposureIndex = 0x0000_8832,
+    ISOSpeed = 0x0000_8833,
+    ISOSpeedLatitudeyyy = 0x0000_8834,
+    ISOSpeedLatitudezzz = 0x0000_8835,
     ExifVersion = 0x0000_9000,
     DateTimeOriginal = 0x0000_9003,
     DateTimeDigitized = 0x0000_9004,
+    OffsetTime = 0x0000_9010,
+    OffsetTimeOriginal = 0x0000_9011,
+    OffsetTimeDigitized = 0x0000_9012,
+    ComponentsConfiguration = 0x0000_9101,
+    CompressedBitsPerPixel = 0x0000_9102,
     ShutterSpeedValue = 0x0000_9201,
     ApertureValue = 0x0000_9202,
     BrightnessValue = 0x0000_9203,
@@ -121,9 +552,19 @@ pub enum ExifTag {
     SubjectArea = 0x0000_9214,
     MakerNote = 0x0000_927c,
     UserComment = 0x0000_9286,
+    SubSecTime = 0x0000_9290,
+    SubSecTimeOriginal = 0x0000_9291,
+    SubSecTimeDigitized = 0x0000_9292,
+    Temperature = 0x0000_9400,
+    Humidity = 0x0000_9401,
+    Pressure = 0x0000_9402,
+    WaterDepth = 0x0000_9403,
+    Acceleration = 0x0000_9404,
+    CameraElevationAngle = 0x0000_9405,
     FlashPixVersion = 0x0000_a000,
     ColorSpace = 0x0000_a001,
     RelatedSoundFile = 0x0000_a004,
+    InteroperabilityOffset = 0x0000_a005,
     FlashEnergy = 0x0000_a20b,
     FocalPlaneXResolution = 0x0000_a20e,
     FocalPlaneYResolution = 0x0000_a20f,
@@ -147,42 +588,135 @@ pub enum ExifTag {
     DeviceSettingDescription = 0x0000_a40b,
     SubjectDistanceRange = 0x0000_a40c,
     ImageUniqueID = 0x0000_a420,
+    CameraOwnerName = 0x0000_a430,
+    BodySerialNumber = 0x0000_a431,
     LensSpecification = 0x0000_a432,
     LensMake = 0x0000_a433,
     LensModel = 0x0000_a434,
+    LensSerialNumber = 0x0000_a435,
+    ImageTitle = 0x0000_a436,
+    Photographer = 0x0000_a437,
+    ImageEditor = 0x0000_a438,
+    CameraFirmware = 0x0000_a439,
+    RAWDevelopingSoftware = 0x0000_a43a,
+    ImageEditingSoftware = 0x0000_a43b,
+    MetadataEditingSoftware = 0x0000_a43c,
+    CompositeImage = 0x0000_a460,
+    SourceImageNumberOfCompositeImage = 0x0000_a461,
+    SourceExposureTimesOfCompositeImage = 0x0000_a462,
     Gamma = 0xa500,
+    DNGVersion = 0x0000_c612,
+    DNGBackwardVersion = 0x0000_c613,
+    UniqueCameraModel = 0x0000_c614,
+    LinearizationTable = 0x0000_c618,
+    BlackLevelRepeatDim = 0x0000_c619,
+    BlackLevel = 0x0000_c61a,
+    WhiteLevel = 0x0000_c61d,
+    DefaultScale = 0x0000_c61e,
+    DefaultCropOrigin = 0x0000_c61f,
+    DefaultCropSize = 0x0000_c620,
+    ColorMatrix1 = 0x0000_c621,
+    ColorMatrix2 = 0x0000_c622,
+    CameraCalibration1 = 0x0000_c623,
+    CameraCalibration2 = 0x0000_c624,
+    AnalogBalance = 0x0000_c627,
+    AsShotNeutral = 0x0000_c628,
+    AsShotWhiteXY = 0x0000_c629,
+    BaselineExposure = 0x0000_c62a,
+    CameraSerialNumber = 0x0000_c62f,
+    LensInfo = 0x0000_c630,
+    DNGPrivateData = 0x0000_c634,
+    CalibrationIlluminant1 = 0x0000_c65a,
+    CalibrationIlluminant2 = 0x0000_c65b,
+    ActiveArea = 0x0000_c68d,
+    ProfileName = 0x0000_c6f8,
+    ProfileEmbedPolicy = 0x0000_c6fd,
+    ProfileCopyright = 0x0000_c6fe,
+    ForwardMatrix1 = 0x0000_c714,
+    ForwardMatrix2 = 0x0000_c715,
+    OpcodeList1 = 0x0000_c740,
+    OpcodeList2 = 0x0000_c741,
+    OpcodeList3 = 0x0000_c74e,
+    CalibrationIlluminant3 = 0x0000_cd31,
+    CameraCalibration3 = 0x0000_cd32,
+    ColorMatrix3 = 0x0000_cd33,
+    ForwardMatrix3 = 0x0000_cd34,
+    IlluminantData1 = 0x0000_cd35,
+    IlluminantData2 = 0x0000_cd36,
+    IlluminantData3 = 0x0000_cd37,
+
+    GPSVersionID = 0x0003_0000,
+    GPSLatitudeRef = 0x0003_0001,
+    GPSLatitude = 0x0003_0002,
+    GPSLongitudeRef = 0x0003_0003,
+    GPSLongitude = 0x0003_0004,
+    GPSAltitudeRef = 0x0003_0005,
+    GPSAltitude = 0x0003_0006,
+    GPSTimeStamp = 0x0003_0007,
+    GPSSatellites = 0x0003_0008,
+    GPSStatus = 0x0003_0009,
+    GPSMeasureMode = 0x0003_000a,
+    GPSDOP = 0x0003_000b,
+    GPSSpeedRef = 0x0003_000c,
+    GPSSpeed = 0x0003_000d,
+    GPSTrackRef = 0x0003_000e,
+    GPSTrack = 0x0003_000f,
+    GPSImgDirectionRef = 0x0003_0010,
+    GPSImgDirection = 0x0003_0011,
+    GPSMapDatum = 0x0003_0012,
+    GPSDestLatitudeRef = 0x0003_0013,
+    GPSDestLatitude = 0x0003_0014,
+    GPSDestLongitudeRef = 0x0003_0015,
+    GPSDestLongitude = 0x0003_0016,
+    GPSDestBearingRef = 0x0003_0017,
+    GPSDestBearing = 0x0003_0018,
+    GPSDestDistanceRef = 0x0003_0019,
+    GPSDestDistance = 0x0003_001a,
+    GPSProcessingMethod = 0x0003_001b,
+    GPSAreaInformation = 0x0003_001c,
+    GPSDateStamp = 0x0003_001d,
+    GPSDifferential = 0x0003_001e,
+
+    InteroperabilityIndex = 0x0004_0001,
+    InteroperabilityVersion = 0x0004_0002,
+    RelatedImageFileFormat = 0x0004_1000,
+    RelatedImageWidth = 0x0004_1001,
+    RelatedImageLength = 0x0004_1002,
+
+    MPFVersion = 0x0005_b000,
+    NumberOfImages = 0x0005_b001,
+    MPEntry = 0x0005_b002,
+    ImageUIDList = 0x0005_b003,
+    TotalFrames = 0x0005_b004,
+    MPIndividualNum = 0x0005_b101,
+    PanOrientation = 0x0005_b201,
+    PanOverlapH = 0x0005_b202,
+    PanOverlapV = 0x0005_b203,
+    BaseViewpointNum = 0x0005_b204,
+    ConvergenceAngle = 0x0005_b205,
+    BaselineLength = 0x0005_b206,
+    VerticalDivergence = 0x0005_b207,
+    AxisDistanceX = 0x0005_b208,
+    AxisDistanceY = 0x0005_b209,
+    AxisDistanceZ = 0x0005_b20a,
+    YawAngle = 0x0005_b20b,
+    PitchAngle = 0x0005_b20c,
+    RollAngle = 0x0005_b20d,
 
-    GPSVersionID = 0x00000,
-    GPSLatitudeRef = 0x00001,
-    GPSLatitude = 0x00002,
-    GPSLongitudeRef = 0x00003,
-    GPSLongitude = 0x00004,
-    GPSAltitudeRef = 0x00005,
-    GPSAltitude = 0x00006,
-    GPSTimeStamp = 0x00007,
-    GPSSatellites = 0x00008,
-    GPSStatus = 0x00009,
-    GPSMeasureMode = 0x0000a,
-    GPSDOP = 0x0000b,
-    GPSSpeedRef = 0x0000c,
-    GPSSpeed = 0x0000d,
-    GPSTrackRef = 0x0000e,
-    GPSTrack = 0x0000f,
-    GPSImgDirectionRef = 0x0000_0010,
-    GPSImgDirection = 0x0000_0011,
-    GPSMapDatum = 0x0000_0012,
-    GPSDestLatitudeRef = 0x0000_0013,
-    GPSDestLatitude = 0x0000_0014,
-    GPSDestLongitudeRef = 0x0000_0015,
-    GPSDestLongitude = 0x0000_0016,
-    GPSDestBearingRef = 0x0000_0017,
-    GPSDestBearing = 0x0000_0018,
-    GPSDestDistanceRef = 0x0000_0019,
-    GPSDestDistance = 0x0000_001a,
-    GPSProcessingMethod = 0x0000_001b,
-    GPSAreaInformation = 0x0000_001c,
-    GPSDateStamp = 0x0000_001d,
-    GPSDifferential = 0x0000_001e,
+    CanonCameraSettings = 0x0002_0001,
+    CanonFocalLength = 0x0002_0002,
+    CanonShotInfo = 0x0002_0004,
+    CanonImageType = 0x0002_0006,
+    CanonFirmwareVersion = 0x0002_0007,
+    CanonFileNumber = 0x0002_0008,
+    CanonOwnerName = 0x0002_0009,
+    CanonSerialNumber = 0x0002_000c,
+    CanonModelID = 0x0002_0010,
+    CanonAFInfo = 0x0002_0012,
+    CanonAFInfo2 = 0x0002_0026,
+    CanonFileInfo = 0x0002_0093,
+    CanonLensModel = 0x0002_0095,
+    CanonInternalSerialNumber = 0x0002_0096,
 }
 
 impl Eq for ExifTag {}
@@ -193,33 +727,75 @@ impl fmt::Display for ExifTag {
             f,
             "{}",
             match *self {
+                ExifTag::NewSubfileType => "Subfile type",
+                ExifTag::ImageWidth => "Image width",
+                ExifTag::ImageLength => "Image height",
+                ExifTag::BitsPerSample => "Bits per sample",
+                ExifTag::Compression => "Compression",
+                ExifTag::PhotometricInterpretation => "Photometric interpretation",
+                ExifTag::DocumentName => "Document name",
                 ExifTag::ImageDescription => "Image Description",
                 ExifTag::Make => "Manufacturer",
                 ExifTag::HostComputer => "Host computer",
                 ExifTag::Model => "Model",
+                ExifTag::StripOffsets => "Offsets of strips",
                 ExifTag::Orientation => "Orientation",
+                ExifTag::SamplesPerPixel => "Samples per pixel",
+                ExifTag::RowsPerStrip => "Rows per strip",
+                ExifTag::StripByteCounts => "Sizes of strips",
                 ExifTag::XResolution => "X Resolution",
                 ExifTag::YResolution => "Y Resolution",
+                ExifTag::PlanarConfiguration => "Planar configuration",
                 ExifTag::ResolutionUnit => "Resolution Unit",
                 ExifTag::Software => "Software",
                 ExifTag::DateTime => "Image date",
+                ExifTag::Artist => "Artist",
+                ExifTag::Predictor => "Predictor",
                 ExifTag::WhitePoint => "White Point",
                 ExifTag::PrimaryChromaticities => "Primary Chromaticities",
+                ExifTag::TileWidth => "Tile width",
+                ExifTag::TileLength => "Tile height",
+                ExifTag::TileOffsets => "Offsets of tiles",
+                ExifTag::TileByteCounts => "Sizes of tiles",
+                ExifTag::SubIFDs => "Offsets of sub-IFDs",
+                ExifTag::ExtraSamples => "Extra samples",
+                ExifTag::SampleFormat => "Sample format",
                 ExifTag::YCbCrCoefficients => "YCbCr Coefficients",
+                ExifTag::YCbCrSubSampling => "YCbCr subsampling",
+                ExifTag::YCbCrPositioning => "YCbCr positioning",
+                ExifTag::JPEGInterchangeFormat => "Offset of JPEG thumbnail",
+                ExifTag::JPEGInterchangeFormatLength => "Size of JPEG thumbnail",
                 ExifTag::ReferenceBlackWhite => "Reference Black/White",
+                ExifTag::Rating => "Rating",
+                ExifTag::RatingPercent => "Rating percent",
                 ExifTag::Copyright => "Copyright",
                 ExifTag::ExifOffset => "This image has an Exif SubIFD",
                 ExifTag::GPSOffset => "This image has a GPS SubIFD",
+                ExifTag::XPTitle => "Title (Windows)",
+                ExifTag::XPComment => "Comment (Windows)",
+                ExifTag::XPAuthor => "Author (Windows)",
+                ExifTag::XPKeywords => "Keywords (Windows)",
+                ExifTag::XPSubject => "Subject (Windows)",
                 ExifTag::ExposureTime => "Exposure time",
                 ExifTag::SensitivityType => "Sensitivity type",
                 ExifTag::FNumber => "Aperture",
                 ExifTag::ExposureProgram => "Exposure program",
                 ExifTag::SpectralSensitivity => "Spectral sensitivity",
                 ExifTag::ISOSpeedRatings => "ISO speed ratings",
+                ExifTag::StandardOutputSensitivity => "Standard output sensitivity",
+                ExifTag::RecommendedExposureIndex => "Recommended exposure index",
+                ExifTag::ISOSpeed => "ISO speed",
+                ExifTag::ISOSpeedLatitudeyyy => "ISO speed latitude yyy",
+                ExifTag::ISOSpeedLatitudezzz => "ISO speed latitude zzz",
                 ExifTag::OECF => "OECF",
                 ExifTag::ExifVersion => "Exif version",
                 ExifTag::DateTimeOriginal => "Date of original image",
                 ExifTag::DateTimeDigitized => "Date of image digitalization",
+                ExifTag::OffsetTime => "Time zone of image date",
+                ExifTag::OffsetTimeOriginal => "Time zone of original image",
+                ExifTag::OffsetTimeDigitized => "Time zone of image digitalization",
+                ExifTag::ComponentsConfiguration => "Components configuration",
+                ExifTag::CompressedBitsPerPixel => "Compressed bits per pixel",
                 ExifTag::ShutterSpeedValue => "Shutter speed",
                 ExifTag::ApertureValue => "Aperture value",
                 ExifTag::BrightnessValue => "Brightness value",
@@ -233,10 +809,20 @@ impl fmt::Display for ExifTag {
                 ExifTag::SubjectArea => "Subject area",
                 ExifTag::MakerNote => "Maker note",
                 ExifTag::UserComment => "User comment",
+                ExifTag::SubSecTime => "Subseconds of image date",
+                ExifTag::SubSecTimeOriginal => "Subseconds of original image",
+                ExifTag::SubSecTimeDigitized => "Subseconds of image digitalization",
+                ExifTag::Temperature => "Temperature",
+                ExifTag::Humidity => "Humidity",
+                ExifTag::Pressure => "Pressure",
+                ExifTag::WaterDepth => "Water depth",
+                ExifTag::Acceleration => "Acceleration",
+                ExifTag::CameraElevationAngle => "Camera elevation angle",
                 ExifTag::FlashPixVersion => "Flashpix version",
                 ExifTag::ColorSpace => "Color space",
                 ExifTag::FlashEnergy => "Flash energy",
                 ExifTag::RelatedSoundFile => "Related sound file",
+                ExifTag::InteroperabilityOffset => "This image has an Interoperability IFD",
                 ExifTag::FocalPlaneXResolution => "Focal plane X resolution",
                 ExifTag::FocalPlaneYResolution => "Focal plane Y resolution",
                 ExifTag::FocalPlaneResolutionUnit => "Focal plane resolution unit",
@@ -259,10 +845,62 @@ impl fmt::Display for ExifTag {
                 ExifTag::LensSpecification => "Lens specification",
                 ExifTag::LensMake => "Lens manufacturer",
                 ExifTag::LensModel => "Lens model",
+                ExifTag::LensSerialNumber => "Lens serial number",
+                ExifTag::ImageTitle => "Image title",
+                ExifTag::Photographer => "Photographer",
+                ExifTag::ImageEditor => "Image editor",
+                ExifTag::CameraFirmware => "Camera firmware",
+                ExifTag::RAWDevelopingSoftware => "RAW developing software",
+                ExifTag::ImageEditingSoftware => "Image editing software",
+                ExifTag::MetadataEditingSoftware => "Metadata editing software",
                 ExifTag::Gamma => "Gamma",
+                ExifTag::DNGVersion => "DNG version",
+                ExifTag::DNGBackwardVersion => "DNG backward version",
+                ExifTag::UniqueCameraModel => "Unique camera model",
+                ExifTag::LinearizationTable => "Linearization table",
+                ExifTag::BlackLevelRepeatDim => "Black level repeat dimensions",
+                ExifTag::BlackLevel => "Black level",
+                ExifTag::WhiteLevel => "White level",
+                ExifTag::DefaultScale => "Default scale",
+                ExifTag::DefaultCropOrigin => "Default crop origin",
+                ExifTag::DefaultCropSize => "Default crop size",
+                ExifTag::ColorMatrix1 => "Color matrix 1",
+                ExifTag::ColorMatrix2 => "Color matrix 2",
+                ExifTag::CameraCalibration1 => "Camera calibration 1",
+                ExifTag::CameraCalibration2 => "Camera calibration 2",
+                ExifTag::AnalogBalance => "Analog balance",
+                ExifTag::AsShotNeutral => "As shot neutral",
+                ExifTag::AsShotWhiteXY => "As shot white point",
+                ExifTag::BaselineExposure => "Baseline exposure",
+                ExifTag::CameraSerialNumber => "Camera serial number (DNG)",
+                ExifTag::LensInfo => "Lens information",
+                ExifTag::DNGPrivateData => "DNG private data",
+                ExifTag::CalibrationIlluminant1 => "Calibration illuminant 1",
+                ExifTag::CalibrationIlluminant2 => "Calibration illuminant 2",
+                ExifTag::ActiveArea => "Active area",
+                ExifTag::ProfileName => "Profile name",
+                ExifTag::ProfileEmbedPolicy => "Profile embed policy",
+                ExifTag::ProfileCopyright => "Profile copyright",
+                ExifTag::ForwardMatrix1 => "Forward matrix 1",
+                ExifTag::ForwardMatrix2 => "Forward matrix 2",
+                ExifTag::OpcodeList1 => "Opcode list 1",
+                ExifTag::OpcodeList2 => "Opcode list 2",
+                ExifTag::OpcodeList3 => "Opcode list 3",
+                ExifTag::CalibrationIlluminant3 => "Calibration illuminant 3",
+                ExifTag::CameraCalibration3 => "Camera calibration 3",
+                ExifTag::ColorMatrix3 => "Color matrix 3",
+                ExifTag::ForwardMatrix3 => "Forward matrix 3",
+                ExifTag::IlluminantData1 => "Illuminant data 1",
+                ExifTag::IlluminantData2 => "Illuminant data 2",
+                ExifTag::IlluminantData3 => "Illuminant data 3",
                 ExifTag::DeviceSettingDescription => "Device setting description",
                 ExifTag::SubjectDistanceRange => "Subject distance range",
                 ExifTag::ImageUniqueID => "Image unique ID",
+                ExifTag::CameraOwnerName => "Camera owner",
+                ExifTag::BodySerialNumber => "Camera serial number",
+                ExifTag::CompositeImage => "Composite image",
+                ExifTag::SourceImageNumberOfCompositeImage => "Number of source images",
+                ExifTag::SourceExposureTimesOfCompositeImage => "Exposure times of source images",
                 ExifTag::GPSVersionID => "GPS version ID",
                 ExifTag::GPSLatitudeRef => "GPS latitude ref",
                 ExifTag::GPSLatitude => "GPS latitude",
@@ -294,6 +932,44 @@ impl fmt::Display for ExifTag {
                 ExifTag::GPSAreaInformation => "GPS area information",
                 ExifTag::GPSDateStamp => "GPS date stamp",
                 ExifTag::GPSDifferential => "GPS differential",
+                ExifTag::InteroperabilityIndex => "Interoperability index",
+                ExifTag::InteroperabilityVersion => "Interoperability version",
+                ExifTag::RelatedImageFileFormat => "Related image file format",
+                ExifTag::RelatedImageWidth => "Related image width",
+                ExifTag::RelatedImageLength => "Related image length",
+                ExifTag::MPFVersion => "MPF version",
+                ExifTag::NumberOfImages => "Number of images",
+                ExifTag::MPEntry => "MP entry",
+                ExifTag::ImageUIDList => "Image unique ID list",
+                ExifTag::TotalFrames => "Total number of captured frames",
+                ExifTag::MPIndividualNum => "MP individual image number",
+                ExifTag::PanOrientation => "Panorama scanning orientation",
+                ExifTag::PanOverlapH => "Panorama horizontal overlap",
+                ExifTag::PanOverlapV => "Panorama vertical overlap",
+                ExifTag::BaseViewpointNum => "Base viewpoint number",
+                ExifTag::ConvergenceAngle => "Convergence angle",
+                ExifTag::BaselineLength => "Baseline length",
+                ExifTag::VerticalDivergence => "Divergence angle",
+                ExifTag::AxisDistanceX => "Horizontal axis distance",
+                ExifTag::AxisDistanceY => "Vertical axis distance",
+                ExifTag::AxisDistanceZ => "Collimation axis distance",
+                ExifTag::YawAngle => "Yaw angle",
+                ExifTag::PitchAngle => "Pitch angle",
+                ExifTag::RollAngle => "Roll angle",
+                ExifTag::CanonCameraSettings => "Camera settings (Canon)",
+                ExifTag::CanonFocalLength => "Focal length (Canon)",
+                ExifTag::CanonShotInfo => "Shot information (Canon)",
+                ExifTag::CanonImageType => "Image type (Canon)",
+                ExifTag::CanonFirmwareVersion => "Firmware version (Canon)",
+                ExifTag::CanonFileNumber => "File number (Canon)",
+                ExifTag::CanonOwnerName => "Owner name (Canon)",
+                ExifTag::CanonSerialNumber => "Serial number (Canon)",
+                ExifTag::CanonModelID => "Model ID (Canon)",
+                ExifTag::CanonAFInfo => "AF information (Canon)",
+                ExifTag::CanonAFInfo2 => "AF information (Canon)",
+                ExifTag::CanonFileInfo => "File information (Canon)",
+                ExifTag::CanonLensModel => "Lens model (Canon)",
+                ExifTag::CanonInternalSerialNumber => "Internal serial number (Canon)",
                 ExifTag::UnknownToMe => "Unknown to this library, or manufacturer-specific",
             }
         )
@@ -319,6 +995,16 @@ pub enum IfdFormat {
     IRational = 10,
     F32 = 11,
     F64 = 12,
+    /// IFD of TIFF Technical Note 1, the offset of an IFD
+    Ifd = 13,
+    /// LONG8 of BigTIFF
+    U64 = 16,
+    /// SLONG8 of BigTIFF
+    I64 = 17,
+    /// IFD8 of BigTIFF, the offset of an IFD
+    Ifd64 = 18,
+    /// UTF-8 string of Exif 3.0, NUL-terminated like ASCII
+    Utf8 = 129,
 }
 
 /// Structure that represents a parsed EXIF tag.
@@ -328,6 +1014,8 @@ pub struct ExifEntry {
     /// official standard. Other namespaces accomodate manufacturer-specific tags that
     /// may be embedded in MarkerNote blob tag.
     pub namespace: Namespace,
+    /// IFD that contains the tag
+    pub ifd_kind: IfdKind,
     /// Low-level IFD entry that contains the EXIF tag. The client may look into this
     /// structure to get tag's raw data, or to parse the tag herself if `tag` is `UnknownToMe`.
     pub ifd: IfdEntry,
@@ -363,6 +1051,9 @@ pub enum TagValue {
     U8(Vec<u8>),
     /// ASCII string. (The standard specifies 7-bit ASCII, but this parser accepts UTF-8 strings.)
     Ascii(String),
+    /// UTF-8 string, allowed by Exif 3.0 for text tags that used to be ASCII. It
+    /// also holds the decoded text of the Windows XP tags, e.g. XPKeywords.
+    Utf8(String),
     U16(Vec<u16>),
     U32(Vec<u32>),
     /// Array of `URational` structures (tuples with integer numerator and denominator)
@@ -386,6 +1077,10 @@ pub enum TagValue {
     F32(Vec<f32>),
     /// Array of IEEE 754 floating-points
     F64(Vec<f64>),
+    /// Array of 64-bit integers, found in BigTIFF files. IFD offsets (IFD8)
+    /// are given as such as well.
+    U64(Vec<u64>),
+    I64(Vec<i64>),
     /// Array of bytes with unknown internal structure.
     /// This is different from `Undefined` because `Undefined` is actually a specified
     /// format, while `Unknown` is an unexpected format type. A tag of `Unknown` format
@@ -413,6 +1108,8 @@ impl TagValue {
             TagValue::I8(ref v) => v.get(index).cloned().map(From::from),
             TagValue::I16(ref v) => v.get(index).cloned().map(From::from),
             TagValue::I32(ref v) => v.get(index).cloned().map(From::from),
+            TagValue::U64(ref v) => v.get(index).map(|&n| n as i64),
+            TagValue::I64(ref v) => v.get(index).cloned(),
             _ => None,
         }
     }
@@ -429,6 +1126,8 @@ impl TagValue {
             TagValue::I32(ref v) => v.get(index).cloned().map(From::from),
             TagValue::F32(ref v) => v.get(index).cloned().map(From::from),
             TagValue::F64(ref v) => v.get(index).cloned().map(From::from),
+            TagValue::U64(ref v) => v.get(index).map(|&n| n as f64),
+            TagValue::I64(ref v) => v.get(index).map(|&n| n as f64),
             TagValue::IRational(ref v) => v.get(index).cloned().map(|v| v.value()),
             TagValue::URational(ref v) => v.get(index).cloned().map(|v| v.value()),
             _ => None,
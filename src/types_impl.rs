@@ -1,10 +1,14 @@
+use super::dng::dng_info;
 use super::ifdformat::numarray_to_string;
 use super::lowlevel::*;
+use super::tiff::TiffSource;
 use super::types::*;
+use super::writer::serialize_tiff;
 use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
 use std::io;
+use std::ops::Range;
 
 /// Convert an IFD format code to the IfdFormat enumeration
 pub fn ifdformat_new(n: u16) -> IfdFormat {
@@ -21,17 +25,41 @@ pub fn ifdformat_new(n: u16) -> IfdFormat {
         10 => IfdFormat::IRational,
         11 => IfdFormat::F32,
         12 => IfdFormat::F64,
+        13 => IfdFormat::Ifd,
+        16 => IfdFormat::U64,
+        17 => IfdFormat::I64,
+        18 => IfdFormat::Ifd64,
+        129 => IfdFormat::Utf8,
         _ => IfdFormat::Unknown,
     }
 }
 
+impl IfdKind {
+    /// Namespace of the tags found in this IFD. MakerNote IFDs are reported as
+    /// `Standard`, their actual namespace depends on the manufacturer.
+    pub fn namespace(self) -> Namespace {
+        match self {
+            IfdKind::Gps => Namespace::Gps,
+            IfdKind::Interop => Namespace::Interop,
+            IfdKind::MpIndex | IfdKind::MpAttributes => Namespace::Mpf,
+            _ => Namespace::Standard,
+        }
+    }
+}
+
 impl IfdEntry {
     /// Casts IFD entry data into an offset. Not very useful for the crate client.
     /// The call can't fail, but the caller must be sure that the IFD entry uses
     /// the IFD data area as an offset (i.e. when the tag is a Sub-IFD tag, or when
     /// there are more than 4 bytes of data and it would not fit within IFD).
+    ///
+    /// Offsets of BigTIFF entries are 64-bit, except for LONG sub-IFD pointers.
     pub fn data_as_offset(&self) -> usize {
-        read_u32(self.le, &(self.ifd_data[0..4])) as usize
+        if self.ifd_data.len() >= 8 && (!self.in_ifd() || self.size() == 8) {
+            read_u64(self.le, &(self.ifd_data[0..8])) as usize
+        } else {
+            read_u32(self.le, &(self.ifd_data[0..4])) as usize
+        }
     }
 
     /// Returns the size of an individual element (e.g. U8=1, U16=2...). Every
@@ -41,6 +69,7 @@ impl IfdEntry {
         match self.format {
             IfdFormat::U8 => 1,
             IfdFormat::Ascii => 1,
+            IfdFormat::Utf8 => 1,
             IfdFormat::U16 => 2,
             IfdFormat::U32 => 4,
             IfdFormat::URational => 8,
@@ -51,6 +80,10 @@ impl IfdEntry {
             IfdFormat::IRational => 8,
             IfdFormat::F32 => 4,
             IfdFormat::F64 => 8,
+            IfdFormat::Ifd => 4,
+            IfdFormat::U64 => 8,
+            IfdFormat::I64 => 8,
+            IfdFormat::Ifd64 => 8,
             IfdFormat::Unknown => 1,
         }
     }
@@ -62,26 +95,33 @@ impl IfdEntry {
 
     /// Returns true if data is contained within the IFD structure, false when
     /// data can be found elsewhere in the image (and IFD structure contains the
-    /// data offset, instead of data).
+    /// data offset, instead of data). BigTIFF entries, whose `ifd_data` is 8 bytes
+    /// long, hold up to 8 bytes of data.
     pub fn in_ifd(&self) -> bool {
-        self.length() <= 4
+        self.length() <= self.ifd_data.len().max(4)
     }
 
-    /// Copies data from IFD entry section reserved for data (up to 4 bytes), or
+    /// Copies data from IFD entry section reserved for data (up to 4 bytes, or 8 in
+    /// BigTIFF), or
     /// from another part of the image file (when data wouldn't fit in IFD structure).
     /// In either case, the data member will contain the data of interest after
     /// this call.
     pub fn copy_data(&mut self, contents: &[u8]) -> bool {
+        let mut contents = contents;
+        self.copy_data_from(&mut contents)
+    }
+
+    /// Same as `copy_data()`, for a TIFF block that may be read on demand
+    pub(crate) fn copy_data_from<S: TiffSource>(&mut self, source: &mut S) -> bool {
         if self.in_ifd() {
-            // the 4 bytes from IFD have all data
+            // the 4 (or 8) bytes from IFD have all data
             self.data = self.ifd_data.clone();
             return true;
         }
 
         let offset = self.data_as_offset();
-        if let Some(ext_data) = contents.get(offset..(offset + self.length())) {
-            self.ext_data.clear();
-            self.ext_data.extend(ext_data);
+        if let Some(ext_data) = source.read(offset, self.length()) {
+            self.ext_data = ext_data;
             self.data = self.ext_data.clone();
             return true;
         }
@@ -89,17 +129,300 @@ impl IfdEntry {
     }
 }
 
+impl ExifData {
+    /// Serialize the EXIF entries back into a TIFF block, in little-endian
+    /// byte order if `le` is true, big-endian otherwise. See `serialize_tiff()`.
+    pub fn serialize(&self, le: bool) -> Result<Vec<u8>, ExifError> {
+        serialize_tiff(self.entries.iter().map(|e| &e.ifd), le)
+    }
+
+    /// JPEG thumbnail embedded in IFD1, if any
+    pub fn thumbnail(&self) -> Option<&[u8]> {
+        let offset_tag = ((ExifTag::JPEGInterchangeFormat as u32) & 0xffff) as u16;
+        self.entries
+            .iter()
+            .find(|e| {
+                e.ifd.ifd_kind == IfdKind::Ifd1
+                    && e.ifd.tag == offset_tag
+                    && !e.ifd.ext_data.is_empty()
+            })
+            .map(|e| &e.ifd.ext_data[..])
+    }
+
+    /// Keywords set in Windows Explorer, that the XPKeywords tag separates with
+    /// semicolons
+    pub fn xp_keywords(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| e.tag == ExifTag::XPKeywords)
+            .filter_map(|e| match e.value {
+                TagValue::Utf8(ref s) => Some(s),
+                _ => None,
+            })
+            .flat_map(|s| s.split(';'))
+            .map(|k| k.trim())
+            .filter(|k| !k.is_empty())
+            .collect()
+    }
+
+    /// Color and raw image tags of a DNG file, or None if the image is not a DNG
+    pub fn dng(&self) -> Option<DngInfo> {
+        dng_info(&self.entries)
+    }
+}
+
+impl XmpData {
+    /// Top-level property, given its namespace URI and name, e.g.
+    /// `("http://ns.adobe.com/xap/1.0/", "Rating")`
+    pub fn property(&self, namespace: &str, name: &str) -> Option<&XmpProperty> {
+        self.properties
+            .iter()
+            .find(|p| p.namespace == namespace && p.name == name)
+    }
+}
+
+impl IptcData {
+    /// Value of the first dataset of the given kind
+    pub fn get(&self, tag: IptcTag) -> Option<&str> {
+        self.datasets
+            .iter()
+            .find(|d| d.tag == tag)
+            .map(|d| &d.value[..])
+    }
+
+    /// Values of every dataset of the given kind, for repeatable datasets
+    pub fn get_all(&self, tag: IptcTag) -> Vec<&str> {
+        self.datasets
+            .iter()
+            .filter(|d| d.tag == tag)
+            .map(|d| &d.value[..])
+            .collect()
+    }
+
+    /// Caption, or abstract, describing the image
+    pub fn caption(&self) -> Option<&str> {
+        self.get(IptcTag::Caption)
+    }
+
+    pub fn keywords(&self) -> Vec<&str> {
+        self.get_all(IptcTag::Keywords)
+    }
+
+    /// Names of the creators of the image
+    pub fn byline(&self) -> Vec<&str> {
+        self.get_all(IptcTag::Byline)
+    }
+
+    pub fn city(&self) -> Option<&str> {
+        self.get(IptcTag::City)
+    }
+
+    /// Full name of the country where the image was taken
+    pub fn country(&self) -> Option<&str> {
+        self.get(IptcTag::Country)
+    }
+
+    /// Provider of the image, e.g. a news agency
+    pub fn credit(&self) -> Option<&str> {
+        self.get(IptcTag::Credit)
+    }
+}
+
+impl PhotoshopData {
+    /// Decoded data of the first resource with the given identifier
+    pub fn get(&self, id: u16) -> Option<&PhotoshopValue> {
+        self.resources.iter().find(|r| r.id == id).map(|r| &r.value)
+    }
+
+    pub fn resolution(&self) -> Option<&PhotoshopResolution> {
+        self.resources.iter().find_map(|r| match r.value {
+            PhotoshopValue::Resolution(ref resolution) => Some(resolution),
+            _ => None,
+        })
+    }
+
+    /// Thumbnail of the image, preferring the one of Photoshop 5 and later
+    /// (1036) to the one of Photoshop 4 (1033)
+    pub fn thumbnail(&self) -> Option<&PhotoshopThumbnail> {
+        let thumbnails = self.resources.iter().filter_map(|r| match r.value {
+            PhotoshopValue::Thumbnail(ref thumbnail) => Some(thumbnail),
+            _ => None,
+        });
+        let mut first = None;
+        for thumbnail in thumbnails {
+            if !thumbnail.bgr {
+                return Some(thumbnail);
+            }
+            first = first.or(Some(thumbnail));
+        }
+        first
+    }
+
+    /// True if the image is marked as copyrighted
+    pub fn copyrighted(&self) -> Option<bool> {
+        self.resources.iter().find_map(|r| match r.value {
+            PhotoshopValue::CopyrightFlag(flag) => Some(flag),
+            _ => None,
+        })
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.resources.iter().find_map(|r| match r.value {
+            PhotoshopValue::Url(ref url) => Some(&url[..]),
+            _ => None,
+        })
+    }
+}
+
+impl MpfData {
+    /// Byte ranges of the secondary images within the file, e.g. to extract a
+    /// depth map or a gain map. The primary image is skipped.
+    pub fn secondary_images(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.images
+            .iter()
+            .filter(|i| i.offset != 0)
+            .map(|i| i.range())
+    }
+}
+
+impl MpImage {
+    /// Image type, e.g. 0x030000 for the baseline primary image, 0x010001 and
+    /// 0x010002 for large thumbnails, 0x020002 for disparity (stereo) images,
+    /// 0x020003 for multi-angle images. Depth and gain maps usually have the
+    /// undefined type, 0.
+    pub fn image_type(&self) -> u32 {
+        self.attribute & 0x00ff_ffff
+    }
+
+    /// Image data format, 0 for JPEG
+    pub fn format(&self) -> u8 {
+        ((self.attribute >> 24) & 0x7) as u8
+    }
+
+    pub fn is_dependent_parent(&self) -> bool {
+        self.attribute & 0x8000_0000 != 0
+    }
+
+    pub fn is_dependent_child(&self) -> bool {
+        self.attribute & 0x4000_0000 != 0
+    }
+
+    /// True for the image to be shown when a single image is displayed
+    pub fn is_representative(&self) -> bool {
+        self.attribute & 0x2000_0000 != 0
+    }
+
+    /// Byte range of the image within the file
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset.saturating_add(self.size as usize)
+    }
+}
+
+impl XmpValue {
+    /// Text of a simple value
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            XmpValue::Simple(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Simple items of an array, e.g. the keywords of dc:subject
+    pub fn strings(&self) -> Vec<&str> {
+        match *self {
+            XmpValue::Array(_, ref items) => {
+                items.iter().filter_map(|i| i.value.as_str()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Text of a language alternative in the given language, falling back to
+    /// the "x-default" item, then to the first item. Simple values are returned
+    /// as-is.
+    pub fn localized(&self, lang: &str) -> Option<&str> {
+        match *self {
+            XmpValue::Simple(ref s) => Some(s),
+            XmpValue::Array(XmpArrayKind::Alt, ref items) => {
+                let with_lang = |l: &str| {
+                    items.iter().find(|i| match i.lang {
+                        Some(ref il) => il.eq_ignore_ascii_case(l),
+                        None => false,
+                    })
+                };
+                with_lang(lang)
+                    .or_else(|| with_lang("x-default"))
+                    .or_else(|| items.first())
+                    .and_then(|i| i.value.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// Field of a structure, given its namespace URI and name
+    pub fn field(&self, namespace: &str, name: &str) -> Option<&XmpProperty> {
+        match *self {
+            XmpValue::Struct(ref fields) => fields
+                .iter()
+                .find(|f| f.namespace == namespace && f.name == name),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for XmpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            XmpValue::Simple(ref s) => write!(f, "{}", s),
+            XmpValue::Struct(ref fields) => {
+                write!(f, "{{")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", field)?;
+                }
+                write!(f, "}}")
+            }
+            XmpValue::Array(_, ref items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match item.lang {
+                        Some(ref lang) => write!(f, "[{}] {}", lang, item.value)?,
+                        None => write!(f, "{}", item.value)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for XmpProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}={}", self.prefix, self.name, self.value)
+    }
+}
+
 impl Error for ExifError {
     fn description(&self) -> &str {
         match *self {
             ExifError::IoError(ref e) => e.description(),
             ExifError::FileTypeUnknown => "File type unknown",
             ExifError::JpegWithoutExif(_) => "JPEG without EXIF section",
+            ExifError::PngWithoutExif(_) => "PNG without EXIF chunk",
+            ExifError::WebpWithoutExif(_) => "WebP without EXIF chunk",
+            ExifError::HeifWithoutExif(_) => "HEIF without EXIF item",
+            ExifError::JxlWithoutExif(_) => "JPEG XL without EXIF box",
+            ExifError::PsdWithoutExif(_) => "PSD without EXIF resource",
             ExifError::TiffTruncated => "TIFF truncated at start",
             ExifError::TiffBadPreamble(_) => "TIFF with bad preamble",
             ExifError::IfdTruncated => "TIFF IFD truncated",
             ExifError::ExifIfdTruncated(_) => "TIFF Exif IFD truncated",
             ExifError::ExifIfdEntryNotFound => "TIFF Exif IFD not found",
+            ExifError::SerializeError(_) => "Could not serialize EXIF data",
         }
     }
 }
@@ -110,11 +433,17 @@ impl Display for ExifError {
             ExifError::IoError(ref e) => e.fmt(f),
             ExifError::FileTypeUnknown => write!(f, "File type unknown"),
             ExifError::JpegWithoutExif(ref s) => write!(f, "JPEG without EXIF section: {}", s),
+            ExifError::PngWithoutExif(ref s) => write!(f, "PNG without EXIF chunk: {}", s),
+            ExifError::WebpWithoutExif(ref s) => write!(f, "WebP without EXIF chunk: {}", s),
+            ExifError::HeifWithoutExif(ref s) => write!(f, "HEIF without EXIF item: {}", s),
+            ExifError::JxlWithoutExif(ref s) => write!(f, "JPEG XL without EXIF box: {}", s),
+            ExifError::PsdWithoutExif(ref s) => write!(f, "PSD without EXIF resource: {}", s),
             ExifError::TiffTruncated => write!(f, "TIFF truncated at start"),
             ExifError::TiffBadPreamble(ref s) => write!(f, "TIFF with bad preamble: {}", s),
             ExifError::IfdTruncated => write!(f, "TIFF IFD truncated"),
             ExifError::ExifIfdTruncated(ref s) => write!(f, "TIFF Exif IFD truncated: {}", s),
             ExifError::ExifIfdEntryNotFound => write!(f, "TIFF Exif IFD not found"),
+            ExifError::SerializeError(ref s) => write!(f, "Could not serialize EXIF data: {}", s),
         }
     }
 }
@@ -129,6 +458,7 @@ impl fmt::Display for TagValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             TagValue::Ascii(ref s) => write!(f, "{}", s),
+            TagValue::Utf8(ref s) => write!(f, "{}", s),
             TagValue::U16(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::I16(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::U8(ref a) => write!(f, "{}", numarray_to_string(a)),
@@ -137,6 +467,8 @@ impl fmt::Display for TagValue {
             TagValue::I32(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::F32(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::F64(ref a) => write!(f, "{}", numarray_to_string(a)),
+            TagValue::U64(ref a) => write!(f, "{}", numarray_to_string(a)),
+            TagValue::I64(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::URational(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::IRational(ref a) => write!(f, "{}", numarray_to_string(a)),
             TagValue::Undefined(ref a, _) => write!(f, "{}", numarray_to_string(a)),
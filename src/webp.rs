@@ -0,0 +1,129 @@
+use super::image::strip_exif_preamble;
+use super::lowlevel::*;
+use super::types::*;
+
+/// Chunk of a RIFF container, like WebP
+pub(crate) struct RiffChunk<'a> {
+    /// Chunk FourCC, e.g. b"EXIF"
+    pub kind: &'a [u8],
+    /// Contents of the chunk, excluding FourCC, size and padding
+    pub data: &'a [u8],
+}
+
+/// Iterator over the chunks of a WebP image, that follow the RIFF header
+pub(crate) struct RiffChunks<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+/// Returns true if the buffer starts with the header of a WebP image
+pub(crate) fn is_webp(contents: &[u8]) -> bool {
+    contents.len() >= 12 && &contents[0..4] == b"RIFF" && &contents[8..12] == b"WEBP"
+}
+
+pub(crate) fn webp_chunks(contents: &[u8]) -> RiffChunks<'_> {
+    // the RIFF size may be shorter than the buffer, never trust it to be longer
+    let riff_end = if contents.len() >= 8 {
+        8 + read_u32(true, &contents[4..8]) as usize
+    } else {
+        0
+    };
+    RiffChunks {
+        contents: &contents[..riff_end.min(contents.len())],
+        offset: 12,
+        done: false,
+    }
+}
+
+impl<'a> RiffChunks<'a> {
+    fn read_chunk(&self) -> Result<RiffChunk<'a>, ExifError> {
+        let contents = self.contents;
+        let offset = self.offset;
+
+        if contents.len() < (offset + 8) {
+            return Err(ExifError::WebpWithoutExif(
+                "WebP truncated in chunk header".to_string(),
+            ));
+        }
+
+        let size = read_u32(true, &contents[offset + 4..offset + 8]) as usize;
+
+        if contents.len() - (offset + 8) < size {
+            return Err(ExifError::WebpWithoutExif(
+                "WebP truncated in chunk body".to_string(),
+            ));
+        }
+
+        Ok(RiffChunk {
+            kind: &contents[offset..offset + 4],
+            data: &contents[offset + 8..offset + 8 + size],
+        })
+    }
+}
+
+impl<'a> Iterator for RiffChunks<'a> {
+    type Item = Result<RiffChunk<'a>, ExifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.contents.len() {
+            return None;
+        }
+
+        let chunk = self.read_chunk();
+        match chunk {
+            Ok(ref chunk) => {
+                // chunks are padded to an even size
+                self.offset += 8 + chunk.data.len() + chunk.data.len() % 2;
+            }
+            Err(_) => self.done = true,
+        }
+        Some(chunk)
+    }
+}
+
+/// Find the embedded TIFF in a WebP image (that in turn contains the EXIF data).
+///
+/// Only the extended file format (VP8X) may carry EXIF data. Some encoders
+/// wrongly put the "Exif\0\0" preamble of JPEG before the TIFF block, it is
+/// skipped.
+pub fn find_embedded_tiff_in_webp(contents: &[u8]) -> Result<&[u8], ExifError> {
+    let mut chunks = webp_chunks(contents);
+
+    match chunks.next() {
+        Some(Ok(ref chunk)) if chunk.kind == b"VP8X" => (),
+        Some(Err(e)) => return Err(e),
+        _ => {
+            return Err(ExifError::WebpWithoutExif(
+                "Simple file format, without metadata".to_string(),
+            ))
+        }
+    }
+
+    for chunk in chunks {
+        let chunk = chunk?;
+        if chunk.kind == b"EXIF" {
+            return Ok(strip_exif_preamble(chunk.data));
+        }
+    }
+
+    Err(ExifError::WebpWithoutExif(
+        "No EXIF chunk found".to_string(),
+    ))
+}
+
+/// Find the XMP packet of a WebP image, stored in the "XMP " chunk
+pub(crate) fn find_xmp_in_webp(contents: &[u8]) -> Option<&[u8]> {
+    webp_chunks(contents)
+        .map_while(Result::ok)
+        .find(|chunk| chunk.kind == b"XMP ")
+        .map(|chunk| chunk.data)
+}
+
+/// Find the ICC profile of a WebP image, stored in the ICCP chunk
+pub(crate) fn find_icc_in_webp(contents: &[u8]) -> Option<&[u8]> {
+    webp_chunks(contents)
+        .map_while(Result::ok)
+        .find(|chunk| chunk.kind == b"ICCP")
+        .map(|chunk| chunk.data)
+}
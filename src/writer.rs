@@ -0,0 +1,270 @@
+use super::lowlevel::*;
+use super::types::*;
+
+fn tag_of(tag: ExifTag) -> u16 {
+    ((tag as u32) & 0xffff) as u16
+}
+
+/// Tags that contain offsets of other IFDs. They are not copied from the input,
+/// because the offsets are only valid in the original layout.
+fn is_ifd_pointer(tag: u16) -> bool {
+    tag == tag_of(ExifTag::ExifOffset)
+        || tag == tag_of(ExifTag::GPSOffset)
+        || tag == tag_of(ExifTag::InteroperabilityOffset)
+        || tag == tag_of(ExifTag::SubIFDs)
+}
+
+/// Formats that only exist in BigTIFF, and that are not written
+fn is_bigtiff_format(format: IfdFormat) -> bool {
+    format == IfdFormat::U64 || format == IfdFormat::I64 || format == IfdFormat::Ifd64
+}
+
+/// Copies an entry, checking that its data matches format and count,
+/// and converting the data to the requested endianess.
+fn convert_entry(entry: &IfdEntry, le: bool) -> Result<IfdEntry, ExifError> {
+    if entry.data.len() < entry.length() {
+        return Err(ExifError::SerializeError(format!(
+            "tag {:x} has {} bytes of data, expected {}",
+            entry.tag,
+            entry.data.len(),
+            entry.length()
+        )));
+    }
+
+    let mut entry = entry.clone();
+    // data copied from within the IFD structure is always 4 bytes long (8 in BigTIFF)
+    entry.data.truncate(entry.length());
+    // entries are written to a classic TIFF, that holds 4 bytes of data within the IFD
+    entry.ifd_data.truncate(4);
+
+    if entry.le != le {
+        let unit = match entry.format {
+            // rationals are pairs of 32-bit integers
+            IfdFormat::URational | IfdFormat::IRational => 4,
+            // opaque data is copied as-is
+            IfdFormat::Undefined => 1,
+            _ => entry.size() as usize,
+        };
+        for item in entry.data.chunks_mut(unit) {
+            item.reverse();
+        }
+        entry.le = le;
+    }
+
+    Ok(entry)
+}
+
+/// Makes an entry that points to another IFD. The offset is filled in
+/// by `set_ifd_pointer()`, once the layout is known.
+fn ifd_pointer(tag: ExifTag, ifd_kind: IfdKind, le: bool) -> IfdEntry {
+    IfdEntry {
+        namespace: Namespace::Standard,
+        ifd_kind,
+        tag: tag_of(tag),
+        format: IfdFormat::U32,
+        count: 1,
+        data: vec![0; 4],
+        ifd_data: vec![0; 4],
+        ext_data: Vec::new(),
+        le,
+    }
+}
+
+fn set_ifd_pointer(entries: &mut [IfdEntry], tag: ExifTag, offset: usize) {
+    for entry in entries.iter_mut().filter(|e| e.tag == tag_of(tag)) {
+        entry.data = write_u32(entry.le, offset as u32).to_vec();
+    }
+}
+
+/// Length of an IFD structure plus its out-of-line data, including
+/// the padding that keeps every value at a word boundary.
+/// Empty IFDs are not written, except for IFD0.
+fn ifd_length(entries: &[IfdEntry]) -> usize {
+    if entries.is_empty() {
+        return 0;
+    }
+    let ext_length: usize = entries
+        .iter()
+        .filter(|e| !e.in_ifd())
+        .map(|e| e.length() + e.length() % 2)
+        .sum();
+    2 + entries.len() * 12 + 4 + ext_length
+}
+
+/// Appends an IFD and its out-of-line data. Offsets are relative to the start
+/// of `out`, that must be the start of the TIFF block.
+fn write_ifd(out: &mut Vec<u8>, le: bool, entries: &[IfdEntry], next_ifd: usize) {
+    let ext_offset = out.len() + 2 + entries.len() * 12 + 4;
+    let mut ext_data: Vec<u8> = Vec::new();
+
+    out.extend_from_slice(&write_u16(le, entries.len() as u16));
+    for entry in entries {
+        out.extend_from_slice(&write_u16(le, entry.tag));
+        out.extend_from_slice(&write_u16(le, entry.format as u16));
+        out.extend_from_slice(&write_u32(le, entry.count));
+        if entry.in_ifd() {
+            let mut data = entry.data.clone();
+            data.resize(4, 0);
+            out.extend_from_slice(&data);
+        } else {
+            let offset = ext_offset + ext_data.len();
+            out.extend_from_slice(&write_u32(le, offset as u32));
+            ext_data.extend_from_slice(&entry.data);
+            // pad to word boundary, like ifd_length() expects
+            if entry.data.len() % 2 == 1 {
+                ext_data.push(0);
+            }
+        }
+    }
+    out.extend_from_slice(&write_u32(le, next_ifd as u32));
+    out.extend_from_slice(&ext_data);
+}
+
+/// Serialize IFD entries into a TIFF block, the same structure that is found
+/// at the start of a TIFF file, or embedded in the APP1 segment of a JPEG image.
+/// Byte order of the block is given by `le` (true=LE, false=BE), and entries
+/// stored in the other byte order are converted.
+///
+/// Each entry is written to the IFD given by its `ifd_kind`, and IFDs are sorted
+/// by tag. Entries of `Unknown` format are skipped, as well as entries of the 64-bit
+/// formats of BigTIFF, since the block is a classic TIFF. So are IFDs embedded in
+/// MakerNote, IFDs after IFD1 and Multi-Picture Format IFDs. The offsets of the
+/// Exif SubIFD, GPS IFD and Interoperability IFD are not copied from the input,
+/// they are written again for the new layout.
+/// The same goes for the thumbnail offset in IFD1, the thumbnail itself is taken
+/// from the `ext_data` of the JPEGInterchangeFormat entry.
+///
+/// `Undefined` data is copied as-is, so MakerNote blobs that contain offsets of their
+/// own may not be valid after serialization.
+pub fn serialize_tiff<'a, I>(entries: I, le: bool) -> Result<Vec<u8>, ExifError>
+where
+    I: IntoIterator<Item = &'a IfdEntry>,
+{
+    let mut ifd0: Vec<IfdEntry> = Vec::new();
+    let mut exif: Vec<IfdEntry> = Vec::new();
+    let mut gps: Vec<IfdEntry> = Vec::new();
+    let mut interop: Vec<IfdEntry> = Vec::new();
+    let mut ifd1: Vec<IfdEntry> = Vec::new();
+    let mut thumbnail: Vec<u8> = Vec::new();
+
+    for entry in entries {
+        if entry.format == IfdFormat::Unknown
+            || is_bigtiff_format(entry.format)
+            || is_ifd_pointer(entry.tag)
+        {
+            continue;
+        }
+        let entry = convert_entry(entry, le)?;
+        match entry.ifd_kind {
+            IfdKind::Ifd0 => ifd0.push(entry),
+            IfdKind::Exif => exif.push(entry),
+            IfdKind::Gps => gps.push(entry),
+            IfdKind::Interop => interop.push(entry),
+            IfdKind::Ifd1 => {
+                if entry.tag == tag_of(ExifTag::JPEGInterchangeFormat) {
+                    thumbnail = entry.ext_data.clone();
+                }
+                ifd1.push(entry)
+            }
+            IfdKind::Page(_)
+            | IfdKind::SubIfd(_)
+            | IfdKind::MakerNote
+            | IfdKind::MpIndex
+            | IfdKind::MpAttributes => (),
+        }
+    }
+
+    // the thumbnail is written right after IFD1, and its entries are
+    // dropped if there is no thumbnail to point to
+    ifd1.retain(|e| {
+        (e.tag != tag_of(ExifTag::JPEGInterchangeFormat)
+            && e.tag != tag_of(ExifTag::JPEGInterchangeFormatLength))
+            || !thumbnail.is_empty()
+    });
+    if !thumbnail.is_empty() {
+        let length = write_u32(le, thumbnail.len() as u32).to_vec();
+        for entry in ifd1.iter_mut() {
+            if entry.tag == tag_of(ExifTag::JPEGInterchangeFormatLength) {
+                entry.format = IfdFormat::U32;
+                entry.count = 1;
+                entry.data = length.clone();
+            }
+        }
+    }
+
+    if !interop.is_empty() {
+        exif.push(ifd_pointer(
+            ExifTag::InteroperabilityOffset,
+            IfdKind::Exif,
+            le,
+        ));
+    }
+    if !exif.is_empty() {
+        ifd0.push(ifd_pointer(ExifTag::ExifOffset, IfdKind::Ifd0, le));
+    }
+    if !gps.is_empty() {
+        ifd0.push(ifd_pointer(ExifTag::GPSOffset, IfdKind::Ifd0, le));
+    }
+
+    for ifd in [&mut ifd0, &mut exif, &mut interop, &mut gps, &mut ifd1].iter_mut() {
+        if ifd.len() > 0xffff {
+            return Err(ExifError::SerializeError(format!(
+                "{} entries do not fit in an IFD",
+                ifd.len()
+            )));
+        }
+        ifd.sort_by_key(|e| e.tag);
+    }
+
+    // Layout: TIFF header, IFD0, Exif SubIFD, Interoperability IFD, GPS IFD,
+    // IFD1 and thumbnail, each IFD followed by its own out-of-line data
+    let ifd0_offset = 8;
+    let exif_offset = ifd0_offset + ifd_length(&ifd0);
+    let interop_offset = exif_offset + ifd_length(&exif);
+    let gps_offset = interop_offset + ifd_length(&interop);
+    let ifd1_offset = gps_offset + ifd_length(&gps);
+    let thumbnail_offset = ifd1_offset + ifd_length(&ifd1);
+    let end = thumbnail_offset + thumbnail.len();
+
+    if end > u32::MAX as usize {
+        return Err(ExifError::SerializeError(format!(
+            "TIFF block of {} bytes is too large",
+            end
+        )));
+    }
+
+    set_ifd_pointer(&mut ifd0, ExifTag::ExifOffset, exif_offset);
+    set_ifd_pointer(&mut ifd0, ExifTag::GPSOffset, gps_offset);
+    set_ifd_pointer(&mut exif, ExifTag::InteroperabilityOffset, interop_offset);
+    set_ifd_pointer(&mut ifd1, ExifTag::JPEGInterchangeFormat, thumbnail_offset);
+
+    let mut out: Vec<u8> = Vec::with_capacity(end);
+    if le {
+        out.extend_from_slice(b"II");
+    } else {
+        out.extend_from_slice(b"MM");
+    }
+    out.extend_from_slice(&write_u16(le, 42));
+    out.extend_from_slice(&write_u32(le, ifd0_offset as u32));
+
+    if ifd1.is_empty() {
+        write_ifd(&mut out, le, &ifd0, 0);
+    } else {
+        write_ifd(&mut out, le, &ifd0, ifd1_offset);
+    }
+    if !exif.is_empty() {
+        write_ifd(&mut out, le, &exif, 0);
+    }
+    if !interop.is_empty() {
+        write_ifd(&mut out, le, &interop, 0);
+    }
+    if !gps.is_empty() {
+        write_ifd(&mut out, le, &gps, 0);
+    }
+    if !ifd1.is_empty() {
+        write_ifd(&mut out, le, &ifd1, 0);
+        out.extend_from_slice(&thumbnail);
+    }
+
+    Ok(out)
+}
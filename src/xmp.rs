@@ -0,0 +1,178 @@
+use super::types::*;
+use roxmltree::{Document, Node};
+use std::ops::Range;
+
+static RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+static XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+static XMP_NOTE_NS: &str = "http://ns.adobe.com/xmp/note/";
+
+/// TIFF tag that contains the XMP packet, in IFD0
+pub(crate) const XMP_TAG: u16 = 700;
+
+/// Finds the XMP packet of a TIFF image, stored as a byte array in IFD0
+pub(crate) fn find_xmp_in_tiff(entries: &[ExifEntry]) -> Option<&[u8]> {
+    entries
+        .iter()
+        .find(|e| e.ifd.ifd_kind == IfdKind::Ifd0 && e.ifd.tag == XMP_TAG)
+        .map(|e| &e.ifd.data[..])
+}
+
+/// Text of an XMP packet, that must be UTF-8
+fn packet_text(packet: &[u8]) -> Result<&str, String> {
+    // some writers terminate or pad the packet with NULs
+    let end = packet.iter().rposition(|&b| b != 0).map_or(0, |p| p + 1);
+    std::str::from_utf8(&packet[..end]).map_err(|e| format!("XMP packet is not valid UTF-8: {}", e))
+}
+
+/// Parses an XMP packet, given as UTF-8 RDF/XML
+pub(crate) fn parse_xmp(packet: &[u8]) -> Result<XmpData, String> {
+    let text = packet_text(packet)?;
+    let doc = Document::parse(text).map_err(|e| format!("XMP packet is not valid XML: {}", e))?;
+
+    let mut properties: Vec<XmpProperty> = Vec::new();
+    for rdf in doc.descendants().filter(|n| is_rdf(*n, "RDF")) {
+        for description in rdf.children().filter(|n| is_rdf(*n, "Description")) {
+            properties.extend(fields(description));
+        }
+    }
+
+    Ok(XmpData {
+        packet: text.to_string(),
+        properties,
+    })
+}
+
+/// Finds the properties of an XMP packet selected by `select`, given their
+/// namespace and name. Returns the byte ranges, within the packet, of the
+/// elements and attributes of rdf:Description that hold them.
+pub(crate) fn find_xmp_properties<F>(packet: &[u8], select: F) -> Result<Vec<Range<usize>>, String>
+where
+    F: Fn(&str, &str) -> bool,
+{
+    let text = packet_text(packet)?;
+    let doc = Document::parse(text).map_err(|e| format!("XMP packet is not valid XML: {}", e))?;
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for description in doc.descendants().filter(|n| is_rdf(*n, "Description")) {
+        for attribute in description.attributes() {
+            if select(attribute.namespace().unwrap_or(""), attribute.name()) {
+                ranges.push(attribute.range());
+            }
+        }
+        for property in description.children().filter(|n| n.is_element()) {
+            let name = property.tag_name();
+            if select(name.namespace().unwrap_or(""), name.name()) {
+                ranges.push(property.range());
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// GUID of the extended XMP packet, that holds the properties that did not fit
+/// in the main packet of a JPEG image
+pub(crate) fn extended_xmp_guid(xmp: &XmpData) -> Option<&str> {
+    xmp.property(XMP_NOTE_NS, "HasExtendedXMP")
+        .and_then(|p| p.value.as_str())
+}
+
+/// Adds the properties of the extended XMP packet to those of the main packet
+pub(crate) fn merge_extended_xmp(xmp: &mut XmpData, extended: &[u8]) -> Result<(), String> {
+    let extended = parse_xmp(extended)?;
+    xmp.properties.extend(extended.properties);
+    Ok(())
+}
+
+fn is_rdf(node: Node<'_, '_>, name: &str) -> bool {
+    node.has_tag_name((RDF_NS, name))
+}
+
+fn lang(node: Node<'_, '_>) -> Option<String> {
+    node.attribute((XML_NS, "lang")).map(|l| l.to_string())
+}
+
+/// Makes a property of a namespaced element or attribute. RDF and XML
+/// attributes (rdf:about, xml:lang...) are syntax, not properties.
+fn property(
+    node: Node<'_, '_>,
+    namespace: Option<&str>,
+    name: &str,
+    lang: Option<String>,
+    value: XmpValue,
+) -> Option<XmpProperty> {
+    let namespace = namespace.filter(|ns| *ns != RDF_NS && *ns != XML_NS)?;
+    Some(XmpProperty {
+        namespace: namespace.to_string(),
+        prefix: node.lookup_prefix(namespace).unwrap_or("").to_string(),
+        name: name.to_string(),
+        lang,
+        value,
+    })
+}
+
+/// Properties of an rdf:Description, or fields of a structure: attributes
+/// are simple values, child elements may be of any kind
+fn fields(node: Node<'_, '_>) -> Vec<XmpProperty> {
+    let attributes = node.attributes().filter_map(|a| {
+        let value = XmpValue::Simple(a.value().to_string());
+        property(node, a.namespace(), a.name(), None, value)
+    });
+    let elements = node.children().filter(|n| n.is_element()).filter_map(|n| {
+        let name = n.tag_name();
+        property(n, name.namespace(), name.name(), lang(n), value(n))
+    });
+    attributes.chain(elements).collect()
+}
+
+/// Value of a property element, or of an array item
+fn value(node: Node<'_, '_>) -> XmpValue {
+    if let Some(uri) = node.attribute((RDF_NS, "resource")) {
+        return XmpValue::Simple(uri.to_string());
+    }
+    if node.attribute((RDF_NS, "parseType")) == Some("Resource") {
+        return qualified_value(node);
+    }
+
+    if let Some(child) = node.first_element_child() {
+        let kind = if is_rdf(child, "Bag") {
+            XmpArrayKind::Bag
+        } else if is_rdf(child, "Seq") {
+            XmpArrayKind::Seq
+        } else if is_rdf(child, "Alt") {
+            XmpArrayKind::Alt
+        } else if is_rdf(child, "Description") {
+            return qualified_value(child);
+        } else {
+            // not valid RDF, but the intent is clear
+            return XmpValue::Struct(fields(node));
+        };
+        let items = child
+            .children()
+            .filter(|n| is_rdf(*n, "li"))
+            .map(|li| XmpItem {
+                lang: lang(li),
+                value: value(li),
+            })
+            .collect();
+        return XmpValue::Array(kind, items);
+    }
+
+    // structure written in the short form, as attributes of an empty element
+    let shorthand = fields(node);
+    if !shorthand.is_empty() {
+        return XmpValue::Struct(shorthand);
+    }
+    XmpValue::Simple(node.text().unwrap_or("").to_string())
+}
+
+/// Value of a structure, or of a qualified value (rdf:value), whose
+/// qualifiers are dropped
+fn qualified_value(node: Node<'_, '_>) -> XmpValue {
+    if let Some(value) = node.attribute((RDF_NS, "value")) {
+        return XmpValue::Simple(value.to_string());
+    }
+    match node.children().find(|n| is_rdf(*n, "value")) {
+        Some(rdf_value) => value(rdf_value),
+        None => XmpValue::Struct(fields(node)),
+    }
+}
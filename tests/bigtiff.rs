@@ -0,0 +1,125 @@
+mod common;
+
+use common::{u16s, u32s, u64s};
+use rexif::*;
+
+/// BigTIFF entry: tag, format, count and value, padded to 8 bytes if inline
+type Entry = (u16, u16, u64, Vec<u8>);
+
+/// Appends an IFD and the values that do not fit in its entries
+fn write_ifd(le: bool, out: &mut Vec<u8>, entries: &[Entry]) {
+    let mut values_offset = out.len() + 8 + entries.len() * 20 + 8;
+    let mut values: Vec<u8> = Vec::new();
+    out.extend(u64s(le, &[entries.len() as u64]));
+    for (tag, format, count, data) in entries {
+        out.extend(u16s(le, &[*tag, *format]));
+        out.extend(u64s(le, &[*count]));
+        if data.len() <= 8 {
+            out.extend(data);
+            out.extend(vec![0; 8 - data.len()]);
+        } else {
+            out.extend(u64s(le, &[values_offset as u64]));
+            values.extend(data);
+            values_offset += data.len();
+        }
+    }
+    out.extend(u64s(le, &[0]));
+    out.extend(values);
+}
+
+fn header(le: bool, ifd0: u64) -> Vec<u8> {
+    let mut out = if le { b"II".to_vec() } else { b"MM".to_vec() };
+    out.extend(u16s(le, &[43, 8, 0]));
+    out.extend(u64s(le, &[ifd0]));
+    out
+}
+
+/// BigTIFF with 8-byte inline values, a LONG8 StripOffsets and an IFD8
+/// ExifOffset, pointing to an Exif IFD that follows IFD0
+fn bigtiff(le: bool, exif_offset: Option<u64>) -> Vec<u8> {
+    let ifd0_length = 8 + 5 * 20 + 8 + 16;
+    let exposure = u32s(le, &[1, 250]);
+    let exif: Vec<Entry> = vec![
+        (0x829a, 5, 1, exposure),
+        (0xa431, 2, 10, b"SN0123456\0".to_vec()),
+    ];
+    let ifd0: Vec<Entry> = vec![
+        (0x010f, 2, 5, b"Acme\0".to_vec()),
+        (0x0110, 2, 8, b"X100 II\0".to_vec()),
+        (0x0111, 16, 1, u64s(le, &[0x1_0000_0000])),
+        (0x013b, 2, 16, b"Jane Doe-Smith.\0".to_vec()),
+        (
+            0x8769,
+            18,
+            1,
+            u64s(le, &[exif_offset.unwrap_or(16 + ifd0_length as u64)]),
+        ),
+    ];
+    let mut out = header(le, 16);
+    write_ifd(le, &mut out, &ifd0);
+    assert_eq!(out.len(), 16 + ifd0_length);
+    write_ifd(le, &mut out, &exif);
+    out
+}
+
+fn readable(exif: &ExifData, tag: ExifTag) -> Option<String> {
+    exif.entries
+        .iter()
+        .find(|e| e.tag == tag)
+        .map(|e| e.value_more_readable.to_string())
+}
+
+#[test]
+fn entries() {
+    for &le in &[true, false] {
+        let (res, warnings) = parse_buffer_quiet(&bigtiff(le, None));
+        let exif = res.unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(exif.mime, "image/tiff");
+        assert_eq!(readable(&exif, ExifTag::Make).as_deref(), Some("Acme"));
+        assert_eq!(readable(&exif, ExifTag::Model).as_deref(), Some("X100 II"));
+        assert_eq!(
+            readable(&exif, ExifTag::Artist).as_deref(),
+            Some("Jane Doe-Smith.")
+        );
+        let strips = exif.entries.iter().find(|e| e.tag == ExifTag::StripOffsets);
+        assert!(matches!(strips.unwrap().value, TagValue::U64(ref v) if v == &[0x1_0000_0000]));
+        let exposure = exif.entries.iter().find(|e| e.tag == ExifTag::ExposureTime);
+        assert_eq!(exposure.unwrap().ifd.ifd_kind, IfdKind::Exif);
+        assert_eq!(exposure.unwrap().value.to_f64(0), Some(0.004));
+        assert_eq!(
+            readable(&exif, ExifTag::BodySerialNumber).as_deref(),
+            Some("SN0123456")
+        );
+    }
+}
+
+#[test]
+fn huge_offsets() {
+    for &le in &[true, false] {
+        // IFD0 offset in the header
+        let mut image = header(le, u64::MAX);
+        image.extend(vec![0; 32]);
+        assert!(parse_buffer_quiet(&image).0.is_err());
+
+        // Exif IFD offset
+        let image = bigtiff(le, Some(u64::MAX - 4));
+        assert!(parse_buffer_quiet(&image).0.is_err());
+        let stripped = strip(
+            &image,
+            &[StripPreset::SerialNumbers, StripPreset::MakerNote],
+        );
+        assert!(stripped.is_err());
+    }
+}
+
+#[test]
+fn strip_serial_numbers() {
+    for &le in &[true, false] {
+        let stripped = strip(&bigtiff(le, None), &[StripPreset::SerialNumbers]).unwrap();
+        let exif = parse_buffer_quiet(&stripped).0.unwrap();
+        assert_eq!(readable(&exif, ExifTag::BodySerialNumber), None);
+        assert!(readable(&exif, ExifTag::ExposureTime).is_some());
+        assert!(!stripped.windows(9).any(|w| w == b"SN0123456"));
+    }
+}
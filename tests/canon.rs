@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use rexif::*;
+
+/// MakerNote IFD of a Canon camera, whose values are laid out after it, as
+/// offsets relative to the TIFF header
+fn makernote(le: bool, offset: usize, file_info: &[u16]) -> Vec<u8> {
+    let mut camera_settings = vec![0; 30];
+    camera_settings[..4].copy_from_slice(&[60, 2, 0, 4]);
+    camera_settings[23..26].copy_from_slice(&[700, 240, 10]);
+    let mut shot_info = vec![0; 30];
+    shot_info[0] = 60;
+    let af_info2 = [
+        48, 2, 3, 3, 6000, 4000, 6000, 4000, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0,
+    ];
+    let entries = vec![
+        short(le, 0x0001, &camera_settings),
+        short(le, 0x0002, &[1, 355, 0, 0]),
+        short(le, 0x0004, &shot_info),
+        ascii(0x0007, "Firmware 1.0.2"),
+        long(le, 0x0008, &[1_234_567]),
+        ascii(0x0009, "Owner Name"),
+        long(le, 0x000c, &[12345]),
+        long(le, 0x0010, &[0x8000_0406]),
+        short(le, 0x0026, &af_info2),
+        short(le, 0x0093, file_info),
+        ascii(0x0095, "EF24-70mm f/2.8"),
+        ascii(0x0096, "HA1234567"),
+    ];
+    let mut out = vec![0; offset];
+    write_ifd(le, &mut out, &entries, 0);
+    out.split_off(offset)
+}
+
+/// TIFF image of the given camera, with a Canon MakerNote in its Exif IFD
+fn canon_tiff(le: bool, make: &str, model: &str, file_info: &[u16]) -> Vec<u8> {
+    let length = makernote(le, 0, file_info).len();
+    let placeholder = vec![0xee; length];
+    let mut image = tiff(
+        le,
+        &Ifds {
+            ifd0: vec![ascii(0x010f, make), ascii(0x0110, model)],
+            exif: vec![undefined(0x927c, &placeholder)],
+            ..Default::default()
+        },
+    );
+    let offset = image
+        .windows(length)
+        .position(|w| w == &placeholder[..])
+        .unwrap();
+    image[offset..offset + length].copy_from_slice(&makernote(le, offset, file_info));
+    image
+}
+
+fn readable(exif: &ExifData, tag: ExifTag) -> Option<String> {
+    exif.entries
+        .iter()
+        .find(|e| e.tag == tag && e.namespace == Namespace::Canon)
+        .map(|e| e.value_more_readable.to_string())
+}
+
+#[test]
+fn makernote_entries() {
+    for &le in &[true, false] {
+        let image = canon_tiff(le, "Canon", "Canon EOS 5D", &[8, 0, 0, 1]);
+        let (res, warnings) = parse_buffer_quiet(&image);
+        let exif = res.unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(
+            readable(&exif, ExifTag::CanonCameraSettings).as_deref(),
+            Some(
+                "Macro mode: Normal, Quality: RAW, Flash mode: Off, Drive mode: Single, \
+                 Focus mode: One-shot AF, Metering mode: Default, Exposure mode: Easy, \
+                 Lens: 24-70 mm"
+            )
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFocalLength).as_deref(),
+            Some("35.5 mm")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonShotInfo).as_deref(),
+            Some(
+                "Target exposure time: 1.0 s, Exposure compensation: +0.0 EV, \
+                 White balance: Auto, Sequence number: 0"
+            )
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFirmwareVersion).as_deref(),
+            Some("Firmware 1.0.2")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFileNumber).as_deref(),
+            Some("123-4567")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonSerialNumber).as_deref(),
+            Some("0000012345")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonModelID).as_deref(),
+            Some("0x80000406")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonAFInfo2).as_deref(),
+            Some("AF area mode: Single-point AF, 3 of 3 AF points valid, in focus: 1 3")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFileInfo).as_deref(),
+            Some("Bracket mode: AEB")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonLensModel).as_deref(),
+            Some("EF24-70mm f/2.8")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonInternalSerialNumber).as_deref(),
+            Some("HA1234567")
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::CanonOwnerName).as_deref(),
+            Some("Owner Name")
+        );
+    }
+}
+
+#[test]
+fn file_info_count() {
+    for &le in &[true, false] {
+        let image = canon_tiff(le, "Canon", "Canon EOS 20D", &[14, 0x1ed1, 0x00d7, 1, 0, 2]);
+        let exif = parse_buffer_quiet(&image).0.unwrap();
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFileInfo).as_deref(),
+            Some("Bracket mode: AEB, Bracket shot number: 2, File number: 123-4567")
+        );
+
+        let model = "Canon EOS-1D Mark II N";
+        let image = canon_tiff(le, "Canon", model, &[8, 0x0001, 0x86a0, 0]);
+        let exif = parse_buffer_quiet(&image).0.unwrap();
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFileInfo).as_deref(),
+            Some("Bracket mode: Off, Shutter count: 100000")
+        );
+
+        // the meaning of the item is unknown for the Mark III
+        let model = "Canon EOS-1D Mark III";
+        let image = canon_tiff(le, "Canon", model, &[8, 0x0001, 0x86a0, 0]);
+        let exif = parse_buffer_quiet(&image).0.unwrap();
+        assert_eq!(
+            readable(&exif, ExifTag::CanonFileInfo).as_deref(),
+            Some("Bracket mode: Off")
+        );
+    }
+}
+
+#[test]
+fn other_makes() {
+    for &le in &[true, false] {
+        let image = canon_tiff(le, "Nikon", "Nikon D850", &[8, 0, 0, 1]);
+        let exif = parse_buffer_quiet(&image).0.unwrap();
+        assert!(exif.entries.iter().all(|e| e.namespace != Namespace::Canon));
+        assert!(exif.entries.iter().any(|e| e.tag == ExifTag::MakerNote));
+    }
+}
+
+#[test]
+fn strip_serial_numbers() {
+    for &le in &[true, false] {
+        let image = canon_tiff(le, "Canon", "Canon EOS 5D", &[8, 0, 0, 1]);
+        let stripped = strip(&image, &[StripPreset::SerialNumbers]).unwrap();
+        assert_eq!(stripped.len(), image.len());
+        assert!(!stripped.windows(9).any(|w| w == b"HA1234567"));
+        assert!(!stripped.windows(10).any(|w| w == b"Owner Name"));
+
+        let (res, warnings) = parse_buffer_quiet(&stripped);
+        let exif = res.unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        for tag in &[
+            ExifTag::CanonSerialNumber,
+            ExifTag::CanonInternalSerialNumber,
+            ExifTag::CanonOwnerName,
+        ] {
+            assert_eq!(readable(&exif, *tag), None, "{:?}", tag);
+        }
+        assert!(readable(&exif, ExifTag::CanonLensModel).is_some());
+        assert!(readable(&exif, ExifTag::CanonFileInfo).is_some());
+
+        // the MakerNote of other makes is left alone
+        let image = canon_tiff(le, "Nikon", "Nikon D850", &[8, 0, 0, 1]);
+        let stripped = strip(&image, &[StripPreset::SerialNumbers]).unwrap();
+        assert_eq!(stripped, image);
+    }
+}
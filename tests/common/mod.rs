@@ -0,0 +1,469 @@
+//! Builders of small images for the tests: TIFF structures, and the containers
+//! that embed them
+
+#![allow(dead_code)]
+
+/// Entry of an IFD: tag, format, count and value, in the byte order of the TIFF
+pub type Entry = (u16, u16, u32, Vec<u8>);
+
+pub fn u16s(le: bool, values: &[u16]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| if le { v.to_le_bytes() } else { v.to_be_bytes() })
+        .collect()
+}
+
+pub fn u32s(le: bool, values: &[u32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| if le { v.to_le_bytes() } else { v.to_be_bytes() })
+        .collect()
+}
+
+pub fn u64s(le: bool, values: &[u64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| if le { v.to_le_bytes() } else { v.to_be_bytes() })
+        .collect()
+}
+
+pub fn ascii(tag: u16, s: &str) -> Entry {
+    let mut data = s.as_bytes().to_vec();
+    data.push(0);
+    (tag, 2, data.len() as u32, data)
+}
+
+pub fn short(le: bool, tag: u16, values: &[u16]) -> Entry {
+    (tag, 3, values.len() as u32, u16s(le, values))
+}
+
+pub fn long(le: bool, tag: u16, values: &[u32]) -> Entry {
+    (tag, 4, values.len() as u32, u32s(le, values))
+}
+
+pub fn rational(le: bool, tag: u16, values: &[(u32, u32)]) -> Entry {
+    let data = values
+        .iter()
+        .flat_map(|&(n, d)| u32s(le, &[n, d]))
+        .collect();
+    (tag, 5, values.len() as u32, data)
+}
+
+pub fn undefined(tag: u16, data: &[u8]) -> Entry {
+    (tag, 7, data.len() as u32, data.to_vec())
+}
+
+/// IFDs of a TIFF structure. Pointers to the Exif, GPS and Interop IFDs and to
+/// the thumbnail are added by `tiff()`.
+#[derive(Default)]
+pub struct Ifds {
+    pub ifd0: Vec<Entry>,
+    pub exif: Vec<Entry>,
+    pub gps: Vec<Entry>,
+    pub interop: Vec<Entry>,
+    pub ifd1: Vec<Entry>,
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// Length of an IFD and of the values that do not fit in its entries
+pub fn ifd_length(entries: &[Entry]) -> usize {
+    let values: usize = entries
+        .iter()
+        .filter(|e| e.3.len() > 4)
+        .map(|e| e.3.len() + e.3.len() % 2)
+        .sum();
+    2 + entries.len() * 12 + 4 + values
+}
+
+fn set_pointer(le: bool, entries: &mut Vec<Entry>, tag: u16, offset: usize) {
+    entries.retain(|e| e.0 != tag);
+    entries.push(long(le, tag, &[offset as u32]));
+    entries.sort_by_key(|e| e.0);
+}
+
+/// Appends an IFD, followed by the values that do not fit in its entries
+pub fn write_ifd(le: bool, out: &mut Vec<u8>, entries: &[Entry], next: usize) {
+    let mut values_offset = out.len() + 2 + entries.len() * 12 + 4;
+    let mut values: Vec<u8> = Vec::new();
+    out.extend(u16s(le, &[entries.len() as u16]));
+    for (tag, format, count, data) in entries {
+        out.extend(u16s(le, &[*tag, *format]));
+        out.extend(u32s(le, &[*count]));
+        if data.len() <= 4 {
+            out.extend(data);
+            out.extend(vec![0; 4 - data.len()]);
+        } else {
+            out.extend(u32s(le, &[values_offset as u32]));
+            values.extend(data);
+            if data.len() % 2 == 1 {
+                values.push(0);
+            }
+            values_offset += data.len() + data.len() % 2;
+        }
+    }
+    out.extend(u32s(le, &[next as u32]));
+    out.extend(values);
+}
+
+/// Lays out the IFDs after the header, in the order IFD0, Exif, GPS, Interop,
+/// IFD1 and thumbnail
+pub fn tiff(le: bool, ifds: &Ifds) -> Vec<u8> {
+    let mut ifd0 = ifds.ifd0.clone();
+    let mut exif = ifds.exif.clone();
+    let gps = ifds.gps.clone();
+    let interop = ifds.interop.clone();
+    let mut ifd1 = ifds.ifd1.clone();
+
+    // pointers are added first with a dummy offset, so that lengths are final
+    if !interop.is_empty() {
+        set_pointer(le, &mut exif, 0xa005, 0);
+    }
+    if !exif.is_empty() {
+        set_pointer(le, &mut ifd0, 0x8769, 0);
+    }
+    if !gps.is_empty() {
+        set_pointer(le, &mut ifd0, 0x8825, 0);
+    }
+    if let Some(ref thumbnail) = ifds.thumbnail {
+        set_pointer(le, &mut ifd1, 0x0201, 0);
+        set_pointer(le, &mut ifd1, 0x0202, thumbnail.len());
+    }
+    let length = |entries: &[Entry]| {
+        if entries.is_empty() {
+            0
+        } else {
+            ifd_length(entries)
+        }
+    };
+    let exif_offset = 8 + length(&ifd0);
+    let gps_offset = exif_offset + length(&exif);
+    let interop_offset = gps_offset + length(&gps);
+    let ifd1_offset = interop_offset + length(&interop);
+    let thumbnail_offset = ifd1_offset + length(&ifd1);
+    if !interop.is_empty() {
+        set_pointer(le, &mut exif, 0xa005, interop_offset);
+    }
+    if !exif.is_empty() {
+        set_pointer(le, &mut ifd0, 0x8769, exif_offset);
+    }
+    if !gps.is_empty() {
+        set_pointer(le, &mut ifd0, 0x8825, gps_offset);
+    }
+    if ifds.thumbnail.is_some() {
+        set_pointer(le, &mut ifd1, 0x0201, thumbnail_offset);
+    }
+
+    let mut out = if le {
+        b"II*\0".to_vec()
+    } else {
+        b"MM\0*".to_vec()
+    };
+    out.extend(u32s(le, &[8]));
+    let next = if ifd1.is_empty() { 0 } else { ifd1_offset };
+    write_ifd(le, &mut out, &ifd0, next);
+    for ifd in [&exif, &gps, &interop, &ifd1] {
+        if !ifd.is_empty() {
+            write_ifd(le, &mut out, ifd, 0);
+        }
+    }
+    if let Some(ref thumbnail) = ifds.thumbnail {
+        out.extend(thumbnail);
+    }
+    out
+}
+
+pub const THUMBNAIL: &[u8] = b"\xff\xd8\xff\xdbTHUMBNAIL\xff\xd9";
+
+/// IFDs with the usual tags of a photo, including location, serial numbers
+/// and a MakerNote
+pub fn sample_ifds(le: bool) -> Ifds {
+    Ifds {
+        ifd0: vec![
+            ascii(0x010f, "Acme"),
+            ascii(0x0110, "Acme X100"),
+            short(le, 0x0112, &[6]),
+            rational(le, 0x011a, &[(72, 1)]),
+            short(le, 0x0128, &[2]),
+            ascii(0x013b, "Jane Doe"),
+        ],
+        exif: vec![
+            rational(le, 0x829a, &[(1, 125)]),
+            rational(le, 0x829d, &[(28, 10)]),
+            ascii(0x9003, "2020:01:02 03:04:05"),
+            undefined(0x927c, b"MAKERNOTEDATA"),
+            ascii(0xa430, "Owner Name"),
+            ascii(0xa431, "SN123456"),
+        ],
+        gps: vec![
+            ascii(0x0001, "N"),
+            rational(le, 0x0002, &[(45, 1), (30, 1), (1234, 100)]),
+            ascii(0x0003, "E"),
+            rational(le, 0x0004, &[(9, 1), (10, 1), (0, 1)]),
+        ],
+        interop: vec![
+            ascii(0x0001, "R98"),
+            undefined(0x0002, b"0100"),
+            short(le, 0x1001, &[160]),
+            long(le, 0x1002, &[120]),
+        ],
+        ifd1: vec![short(le, 0x0103, &[6]), rational(le, 0x011a, &[(72, 1)])],
+        thumbnail: Some(THUMBNAIL.to_vec()),
+    }
+}
+
+pub fn sample_tiff(le: bool) -> Vec<u8> {
+    tiff(le, &sample_ifds(le))
+}
+
+/// Marker segment of a JPEG image
+pub fn segment(marker: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0xff, marker];
+    out.extend(&((data.len() + 2) as u16).to_be_bytes());
+    out.extend(data);
+    out
+}
+
+pub fn exif_segment(tiff: &[u8]) -> Vec<u8> {
+    let mut data = b"Exif\0\0".to_vec();
+    data.extend(tiff);
+    segment(0xe1, &data)
+}
+
+/// JPEG image made of the given segments, followed by a quantization table
+/// and some entropy-coded data
+pub fn jpeg(segments: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = vec![0xff, 0xd8];
+    for s in segments {
+        out.extend(s);
+    }
+    let table: Vec<u8> = (0..65).collect();
+    out.extend(segment(0xdb, &table));
+    out.extend(segment(0xda, &[1, 1, 0, 0, 0x3f, 0]));
+    for _ in 0..10 {
+        out.extend(b"\x12\x34\xff\x00\x56\xff\xd0\x78");
+    }
+    out.extend(&[0xff, 0xd9]);
+    out
+}
+
+/// Photoshop image resource block made of the given resources
+pub fn irb(resources: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (id, data) in resources {
+        out.extend(b"8BIM");
+        out.extend(&id.to_be_bytes());
+        out.extend(&[0, 0]);
+        out.extend(&(data.len() as u32).to_be_bytes());
+        out.extend(*data);
+        if data.len() % 2 == 1 {
+            out.push(0);
+        }
+    }
+    out
+}
+
+pub fn photoshop_segment(irb: &[u8]) -> Vec<u8> {
+    let mut data = b"Photoshop 3.0\0".to_vec();
+    data.extend(irb);
+    segment(0xed, &data)
+}
+
+/// IPTC-IIM dataset of the application record
+pub fn iptc_dataset(number: u8, value: &str) -> Vec<u8> {
+    let mut out = vec![0x1c, 2, number];
+    out.extend(&(value.len() as u16).to_be_bytes());
+    out.extend(value.as_bytes());
+    out
+}
+
+pub const XMP: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="3"/></rdf:RDF></x:xmpmeta>"#;
+
+pub fn xmp_segment(packet: &str) -> Vec<u8> {
+    let mut data = b"http://ns.adobe.com/xap/1.0/\0".to_vec();
+    data.extend(packet.as_bytes());
+    segment(0xe1, &data)
+}
+
+/// Main XMP packet that refers to an extended packet by its GUID
+pub fn xmp_with_extension(guid: &str) -> String {
+    format!(
+        r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmlns:xmpNote="http://ns.adobe.com/xmp/note/" xmp:Rating="3" xmpNote:HasExtendedXMP="{}"/></rdf:RDF></x:xmpmeta>"#,
+        guid
+    )
+}
+
+/// GUID of an extended XMP packet, the MD5 digest of its contents
+pub fn xmp_guid(packet: &[u8]) -> String {
+    format!("{:X}", md5::compute(packet))
+}
+
+/// APP1 segments that hold an extended XMP packet, cut in chunks of
+/// `chunk_size` bytes, written in the given order of chunks
+pub fn extended_xmp_segments(
+    guid: &str,
+    packet: &[u8],
+    chunk_size: usize,
+    order: &[usize],
+) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = packet.chunks(chunk_size).collect();
+    order
+        .iter()
+        .map(|&i| {
+            let mut data = b"http://ns.adobe.com/xmp/extension/\0".to_vec();
+            data.extend(guid.as_bytes());
+            data.extend(&(packet.len() as u32).to_be_bytes());
+            data.extend(&((i * chunk_size) as u32).to_be_bytes());
+            data.extend(chunks[i]);
+            segment(0xe1, &data)
+        })
+        .collect()
+}
+
+/// Minimal ICC profile: a header and an empty tag table
+pub fn icc_profile() -> Vec<u8> {
+    let mut out = vec![0; 132];
+    out[0..4].copy_from_slice(&132u32.to_be_bytes());
+    out[8] = 4;
+    out[12..16].copy_from_slice(b"mntr");
+    out[16..20].copy_from_slice(b"RGB ");
+    out[20..24].copy_from_slice(b"XYZ ");
+    out[36..40].copy_from_slice(b"acsp");
+    out
+}
+
+/// PNG chunk, with a dummy CRC that is not checked by the parser
+pub fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut out = (data.len() as u32).to_be_bytes().to_vec();
+    out.extend(kind);
+    out.extend(data);
+    out.extend(&[0; 4]);
+    out
+}
+
+pub fn png(chunks: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    out.extend(png_chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]));
+    for c in chunks {
+        out.extend(c);
+    }
+    out.extend(png_chunk(
+        b"IDAT",
+        b"\x78\x9c\x63\x60\x00\x00\x00\x02\x00\x01",
+    ));
+    out.extend(png_chunk(b"IEND", b""));
+    out
+}
+
+pub fn riff_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut out = kind.to_vec();
+    out.extend(&(data.len() as u32).to_le_bytes());
+    out.extend(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+pub fn webp(tiff: &[u8]) -> Vec<u8> {
+    let mut body = b"WEBP".to_vec();
+    body.extend(riff_chunk(b"VP8X", &[0x08, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+    body.extend(riff_chunk(b"VP8 ", b"\x01\x02\x03"));
+    body.extend(riff_chunk(b"EXIF", tiff));
+    let mut out = b"RIFF".to_vec();
+    out.extend(&(body.len() as u32).to_le_bytes());
+    out.extend(body);
+    out
+}
+
+/// ISOBMFF box
+pub fn bmff_box(kind: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut out = ((data.len() + 8) as u32).to_be_bytes().to_vec();
+    out.extend(kind);
+    out.extend(data);
+    out
+}
+
+fn full_box(kind: &[u8], version: u8, data: &[u8]) -> Vec<u8> {
+    let mut content = vec![version, 0, 0, 0];
+    content.extend(data);
+    bmff_box(kind, &content)
+}
+
+/// HEIF image with a coded image item, of type `item_type`, and an Exif item
+/// that describes it, stored in the mdat box
+pub fn heif(brand: &[u8], item_type: &[u8], tiff: &[u8]) -> Vec<u8> {
+    heif_with_brands(brand, &[b"mif1", brand], item_type, tiff)
+}
+
+pub fn heif_with_brands(
+    brand: &[u8],
+    compatible: &[&[u8]],
+    item_type: &[u8],
+    tiff: &[u8],
+) -> Vec<u8> {
+    let mut ftyp = brand.to_vec();
+    ftyp.extend(&[0; 4]);
+    for b in compatible {
+        ftyp.extend(*b);
+    }
+    let ftyp = bmff_box(b"ftyp", &ftyp);
+
+    let infe = |id: u16, kind: &[u8]| {
+        let mut data = id.to_be_bytes().to_vec();
+        data.extend(&[0, 0]);
+        data.extend(kind);
+        data.push(0);
+        full_box(b"infe", 2, &data)
+    };
+    let mut iinf = 2u16.to_be_bytes().to_vec();
+    iinf.extend(infe(1, item_type));
+    iinf.extend(infe(2, b"Exif"));
+
+    let mut payload = 6u32.to_be_bytes().to_vec();
+    payload.extend(b"Exif\0\0");
+    payload.extend(tiff);
+
+    let meta = |offset: u32| {
+        let mut iloc = vec![0x44, 0x00, 0, 1, 0, 2, 0, 0, 0, 0, 0, 1];
+        iloc.extend(&offset.to_be_bytes());
+        iloc.extend(&(payload.len() as u32).to_be_bytes());
+        let mut content = full_box(b"hdlr", 0, b"\0\0\0\0pict\0\0\0\0\0\0\0\0\0\0\0\0\0");
+        content.extend(full_box(b"pitm", 0, &[0, 1]));
+        content.extend(full_box(b"iinf", 0, &iinf));
+        content.extend(full_box(
+            b"iref",
+            0,
+            &bmff_box(b"cdsc", &[0, 2, 0, 1, 0, 1]),
+        ));
+        content.extend(full_box(b"iloc", 1, &iloc));
+        full_box(b"meta", 0, &content)
+    };
+    let offset = ftyp.len() + meta(0).len() + 8;
+    let mut out = ftyp;
+    out.extend(meta(offset as u32));
+    out.extend(bmff_box(b"mdat", &payload));
+    out
+}
+
+/// JPEG XL image in the ISOBMFF container, with an Exif box
+pub fn jxl(tiff: &[u8]) -> Vec<u8> {
+    let mut out = b"\0\0\0\x0cJXL \r\n\x87\n".to_vec();
+    out.extend(bmff_box(b"ftyp", b"jxl \0\0\0\0jxl "));
+    let mut exif = vec![0; 4];
+    exif.extend(tiff);
+    out.extend(bmff_box(b"Exif", &exif));
+    out.extend(bmff_box(b"jxlc", b"\xff\x0a\x00"));
+    out
+}
+
+/// PSD file of a 1x1 RGB image, with the given image resource block
+pub fn psd(irb: &[u8]) -> Vec<u8> {
+    let mut out = b"8BPS\0\x01\0\0\0\0\0\0\0\x03\0\0\0\x01\0\0\0\x01\0\x08\0\x03".to_vec();
+    out.extend(&0u32.to_be_bytes());
+    out.extend(&(irb.len() as u32).to_be_bytes());
+    out.extend(irb);
+    // layers and image data
+    out.extend(&0u32.to_be_bytes());
+    out.extend(&[0, 0, 0xff, 0, 0]);
+    out
+}
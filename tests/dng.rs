@@ -0,0 +1,103 @@
+mod common;
+
+use common::*;
+use rexif::*;
+
+fn srational(le: bool, tag: u16, values: &[(i32, i32)]) -> Entry {
+    let data = values
+        .iter()
+        .flat_map(|&(n, d)| u32s(le, &[n as u32, d as u32]))
+        .collect();
+    (tag, 10, values.len() as u32, data)
+}
+
+/// DNG whose IFD0 holds the color tags and points to a preview and to the raw
+/// image through SubIFDs
+fn dng(le: bool) -> Vec<u8> {
+    let preview = vec![long(le, 0x00fe, &[1])];
+    let raw = vec![
+        long(le, 0x00fe, &[0]),
+        long(le, 0x0100, &[4020]),
+        rational(le, 0xc61a, &[(512, 1)]),
+        short(le, 0xc61d, &[16383]),
+        short(le, 0xc61f, &[12, 14]),
+        rational(le, 0xc620, &[(4000, 1), (3000, 1)]),
+        long(le, 0xc68d, &[8, 10, 3008, 4010]),
+    ];
+    let color_matrix = [
+        (7, 10),
+        (-2, 10),
+        (-1, 10),
+        (-3, 10),
+        (11, 10),
+        (2, 10),
+        (0, 1),
+        (1, 10),
+        (6, 10),
+    ];
+    let mut ifd0 = vec![
+        long(le, 0x00fe, &[1]),
+        (0x014a, 4, 2, u32s(le, &[0, 0])),
+        (0xc612, 1, 4, vec![1, 6, 0, 0]),
+        ascii(0xc614, "Foo Bar"),
+        srational(le, 0xc621, &color_matrix),
+        rational(le, 0xc628, &[(1, 2), (1, 1), (2, 3)]),
+        srational(le, 0xc62a, &[(-1, 2)]),
+        short(le, 0xc65a, &[21]),
+        undefined(0xc740, &[1, 2, 3, 4, 5]),
+    ];
+    let preview_offset = 8 + ifd_length(&ifd0);
+    let raw_offset = preview_offset + ifd_length(&preview);
+    ifd0[1].3 = u32s(le, &[preview_offset as u32, raw_offset as u32]);
+
+    let mut out = if le {
+        b"II*\0".to_vec()
+    } else {
+        b"MM\0*".to_vec()
+    };
+    out.extend(u32s(le, &[8]));
+    write_ifd(le, &mut out, &ifd0, 0);
+    write_ifd(le, &mut out, &preview, 0);
+    write_ifd(le, &mut out, &raw, 0);
+    out
+}
+
+#[test]
+fn raw_sub_ifd() {
+    for &le in &[true, false] {
+        let exif = parse_buffer(&dng(le)).unwrap();
+        let info = exif.dng().expect("DNG");
+        assert_eq!(info.version, [1, 6, 0, 0]);
+        assert_eq!(info.unique_camera_model.as_deref(), Some("Foo Bar"));
+        assert_eq!(info.raw_ifd, IfdKind::SubIfd(1));
+        let color_matrix = info.color_matrix1.unwrap();
+        assert_eq!(color_matrix.len(), 9);
+        assert!((color_matrix[1] + 0.2).abs() < 1e-9);
+        assert_eq!(info.calibration_illuminant1, Some(21));
+        assert_eq!(info.baseline_exposure, Some(-0.5));
+        assert_eq!(info.as_shot_neutral.unwrap()[0], 0.5);
+        assert_eq!(info.active_area, Some([8, 10, 3008, 4010]));
+        assert_eq!(info.default_crop_origin, Some([12.0, 14.0]));
+        assert_eq!(info.default_crop_size, Some([4000.0, 3000.0]));
+        assert_eq!(info.black_level, Some(vec![512.0]));
+        assert_eq!(info.white_level, Some(vec![16383]));
+
+        let readable = |tag: ExifTag| {
+            exif.entries
+                .iter()
+                .find(|e| e.tag == tag)
+                .map(|e| &e.value_more_readable[..])
+        };
+        assert_eq!(readable(ExifTag::DNGVersion), Some("1.6.0.0"));
+        assert_eq!(readable(ExifTag::BaselineExposure), Some("-0.50 EV"));
+        assert_eq!(readable(ExifTag::CalibrationIlluminant1), Some("D65"));
+        assert_eq!(readable(ExifTag::DefaultCropSize), Some("4000, 3000"));
+        assert_eq!(readable(ExifTag::OpcodeList1), Some("Blob of 5 bytes"));
+    }
+}
+
+#[test]
+fn not_a_dng() {
+    let exif = parse_buffer(&sample_tiff(true)).unwrap();
+    assert!(exif.dng().is_none());
+}
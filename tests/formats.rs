@@ -0,0 +1,215 @@
+mod common;
+
+use common::*;
+use rexif::*;
+use std::convert::TryInto;
+use std::io::Cursor;
+
+fn summary(exif: &ExifData) -> Vec<String> {
+    exif.entries
+        .iter()
+        .map(|e| format!("{:?} {:?} {}", e.ifd.ifd_kind, e.tag, e.value_more_readable))
+        .collect()
+}
+
+/// Parses an image both from a buffer and from a reader, checking that the
+/// results match the EXIF data of the sample TIFF
+fn check(image: &[u8], mime: &str, le: bool) {
+    let expected = summary(&parse_buffer(&sample_tiff(le)).unwrap());
+    let from_buffer = parse_buffer_quiet(image).0.unwrap();
+    let from_reader = parse_reader_quiet(Cursor::new(image)).0.unwrap();
+    for exif in &[from_buffer, from_reader] {
+        assert_eq!(exif.mime, mime);
+        assert_eq!(summary(exif), expected);
+    }
+}
+
+#[test]
+fn jpeg_and_tiff() {
+    for &le in &[true, false] {
+        check(&sample_tiff(le), "image/tiff", le);
+        check(&jpeg(&[exif_segment(&sample_tiff(le))]), "image/jpeg", le);
+    }
+}
+
+/// ImageMagick raw profile: name, length and hex digits broken in lines
+fn raw_profile(length: usize, data: &[u8]) -> Vec<u8> {
+    let hex: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    let lines: Vec<&str> = hex
+        .as_bytes()
+        .chunks(72)
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect();
+    format!("\nexif\n{:8}\n{}\n", length, lines.join("\n")).into_bytes()
+}
+
+fn text_chunk(profile: &[u8]) -> Vec<u8> {
+    let mut data = b"Raw profile type exif\0".to_vec();
+    data.extend(profile);
+    png_chunk(b"tEXt", &data)
+}
+
+fn compressed_text_chunk(profile: &[u8]) -> Vec<u8> {
+    let mut data = b"Raw profile type exif\0\0".to_vec();
+    data.extend(miniz_oxide::deflate::compress_to_vec_zlib(profile, 6));
+    png_chunk(b"zTXt", &data)
+}
+
+#[test]
+fn png_images() {
+    for &le in &[true, false] {
+        let tiff = sample_tiff(le);
+        check(&png(&[png_chunk(b"eXIf", &tiff)]), "image/png", le);
+
+        let mut exif = b"Exif\0\0".to_vec();
+        exif.extend(&tiff);
+        let profile = raw_profile(exif.len(), &exif);
+        check(&png(&[text_chunk(&profile)]), "image/png", le);
+        check(&png(&[compressed_text_chunk(&profile)]), "image/png", le);
+
+        // the eXIf chunk is preferred
+        let other = tiff_with_make(le, "Other");
+        let image = png(&[
+            text_chunk(&raw_profile(other.len(), &other)),
+            png_chunk(b"eXIf", &tiff),
+        ]);
+        check(&image, "image/png", le);
+    }
+}
+
+fn tiff_with_make(le: bool, make: &str) -> Vec<u8> {
+    tiff(
+        le,
+        &Ifds {
+            ifd0: vec![ascii(0x010f, make)],
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn png_malformed_raw_profiles() {
+    let tiff = sample_tiff(true);
+    let mut bad_digit = raw_profile(tiff.len(), &tiff);
+    let digit = bad_digit.len() - 10;
+    bad_digit[digit] = b'g';
+    let mut bad_length = raw_profile(tiff.len(), &tiff);
+    bad_length[6..14].copy_from_slice(b"    12x4");
+    let profiles = [
+        bad_digit,
+        // more bytes announced than there are digits
+        raw_profile(tiff.len() + 1, &tiff),
+        bad_length,
+        b"\nexif\n".to_vec(),
+    ];
+    for profile in &profiles {
+        for image in &[
+            png(&[text_chunk(profile)]),
+            png(&[compressed_text_chunk(profile)]),
+        ] {
+            match parse_buffer_quiet(image).0 {
+                Err(ExifError::PngWithoutExif(_)) => (),
+                res => panic!("{:?}", res.map(|_| ())),
+            }
+        }
+    }
+
+    // a zTXt chunk that does not inflate
+    let mut data = b"Raw profile type exif\0\0".to_vec();
+    data.extend(b"not zlib");
+    let image = png(&[png_chunk(b"zTXt", &data)]);
+    assert!(matches!(
+        parse_buffer_quiet(&image).0,
+        Err(ExifError::PngWithoutExif(_))
+    ));
+
+    // a valid profile after a malformed one is still found
+    let image = png(&[
+        text_chunk(&profiles[0]),
+        text_chunk(&raw_profile(tiff.len(), &tiff)),
+    ]);
+    check(&image, "image/png", true);
+}
+
+#[test]
+fn jpeg_segments_before_exif() {
+    let icc = segment(0xe2, b"ICC_PROFILE\0\x01\x01");
+    let photoshop = photoshop_segment(&irb(&[(1034, &[1])]));
+    let adobe = segment(0xee, b"Adobe\0\x64\0\0\0\0\x01");
+    for &le in &[true, false] {
+        let image = jpeg(&[
+            icc.clone(),
+            photoshop.clone(),
+            adobe.clone(),
+            xmp_segment(XMP),
+            exif_segment(&sample_tiff(le)),
+        ]);
+        check(&image, "image/jpeg", le);
+    }
+}
+
+#[test]
+fn webp_image() {
+    check(&webp(&sample_tiff(true)), "image/webp", true);
+}
+
+#[test]
+fn short_streams() {
+    for image in &[&b""[..], b"\xff\xd8", b"II*\0", b"RIFF\0\0\0\0WEB"] {
+        let (res, _) = parse_reader_quiet(Cursor::new(image));
+        assert!(res.is_err());
+    }
+}
+
+#[test]
+fn heif_images() {
+    check(
+        &heif(b"heic", b"hvc1", &sample_tiff(true)),
+        "image/heic",
+        true,
+    );
+    check(
+        &heif(b"avif", b"av01", &sample_tiff(false)),
+        "image/avif",
+        false,
+    );
+}
+
+#[test]
+fn heif_with_long_ftyp() {
+    let mut brands: Vec<&[u8]> = vec![b"mif1", b"miaf", b"heic"];
+    brands.extend(vec![&b"MiHB"[..]; 20]);
+    let image = heif_with_brands(b"mif1", &brands, b"hvc1", &sample_tiff(true));
+    check(&image, "image/heic", true);
+}
+
+#[test]
+fn heif_extent_overflow() {
+    let mut image = heif(b"heic", b"hvc1", &sample_tiff(true));
+    // item location with 8-byte base offset and extent offset, that overflow
+    let mut iloc = vec![1, 0, 0, 0, 0x88, 0x80, 0, 1, 0, 2, 0, 0, 0, 0];
+    iloc.extend(&u64::MAX.to_be_bytes());
+    iloc.extend(&[0, 1]);
+    iloc.extend(&2u64.to_be_bytes());
+    iloc.extend(&16u64.to_be_bytes());
+    let iloc = bmff_box(b"iloc", &iloc);
+
+    let start = image.windows(4).position(|w| w == b"iloc").unwrap() - 4;
+    let old_length = u32::from_be_bytes(image[start..start + 4].try_into().unwrap()) as usize;
+    let growth = (iloc.len() - old_length) as u32;
+    image.splice(start..start + old_length, iloc);
+    let meta = image.windows(4).position(|w| w == b"meta").unwrap() - 4;
+    let meta_length = u32::from_be_bytes(image[meta..meta + 4].try_into().unwrap());
+    image[meta..meta + 4].copy_from_slice(&(meta_length + growth).to_be_bytes());
+
+    match parse_buffer_quiet(&image).0 {
+        Err(ExifError::HeifWithoutExif(e)) => assert!(e.contains("out of range"), "{}", e),
+        res => panic!("{:?}", res.map(|_| ())),
+    }
+}
+
+#[test]
+fn jxl_image() {
+    check(&jxl(&sample_tiff(true)), "image/jxl", true);
+    check(&jxl(&sample_tiff(false)), "image/jxl", false);
+}
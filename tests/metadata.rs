@@ -0,0 +1,188 @@
+mod common;
+
+use common::*;
+use rexif::*;
+use std::io::Cursor;
+
+/// Parses an image both from a buffer and from a reader
+fn parse_both(image: &[u8]) -> Vec<ExifResult> {
+    vec![
+        parse_buffer_quiet(image).0,
+        parse_reader_quiet(Cursor::new(image)).0,
+    ]
+}
+
+#[test]
+fn jpeg_with_xmp_only() {
+    let image = jpeg(&[xmp_segment(XMP)]);
+    for res in parse_both(&image) {
+        let exif = res.unwrap();
+        assert!(exif.entries.is_empty());
+        let xmp = exif.xmp.unwrap();
+        let rating = xmp.property("http://ns.adobe.com/xap/1.0/", "Rating");
+        assert_eq!(rating.unwrap().value.as_str(), Some("3"));
+    }
+}
+
+#[test]
+fn jpeg_without_metadata() {
+    let image = jpeg(&[segment(0xe0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0")]);
+    for res in parse_both(&image) {
+        assert!(matches!(res, Err(ExifError::JpegWithoutExif(_))));
+    }
+}
+
+#[test]
+fn broken_exif_is_a_warning() {
+    let image = jpeg(&[exif_segment(b"II*\0\xff\xff\0\0"), xmp_segment(XMP)]);
+    let (res, warnings) = parse_buffer_quiet(&image);
+    let exif = res.unwrap();
+    assert!(exif.entries.is_empty());
+    assert!(exif.xmp.is_some());
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+}
+
+#[test]
+fn jpeg_with_iptc_only() {
+    let mut iptc = iptc_dataset(120, "A caption");
+    iptc.extend(iptc_dataset(25, "first"));
+    iptc.extend(iptc_dataset(25, "second"));
+    let image = jpeg(&[photoshop_segment(&irb(&[(1028, &iptc)]))]);
+    for res in parse_both(&image) {
+        let exif = res.unwrap();
+        assert!(exif.entries.is_empty());
+        let iptc = exif.iptc.unwrap();
+        assert_eq!(iptc.caption(), Some("A caption"));
+        assert_eq!(iptc.keywords(), vec!["first", "second"]);
+    }
+}
+
+#[test]
+fn psd_without_exif() {
+    let iptc = iptc_dataset(120, "A caption");
+    let icc = icc_profile();
+    let image = psd(&irb(&[(1034, &[1]), (1028, &iptc), (1039, &icc)]));
+    for res in parse_both(&image) {
+        let exif = res.unwrap();
+        assert_eq!(exif.mime, "image/vnd.adobe.photoshop");
+        assert!(exif.entries.is_empty());
+        assert_eq!(exif.photoshop.unwrap().copyrighted(), Some(true));
+        assert_eq!(exif.iptc.unwrap().caption(), Some("A caption"));
+        assert_eq!(exif.icc.unwrap().color_space, "RGB");
+    }
+}
+
+#[test]
+fn png_with_icc_only() {
+    let mut iccp = b"sRGB\0\0".to_vec();
+    iccp.extend(miniz_oxide::deflate::compress_to_vec_zlib(
+        &icc_profile(),
+        6,
+    ));
+    let image = png(&[png_chunk(b"iCCP", &iccp)]);
+    for res in parse_both(&image) {
+        let exif = res.unwrap();
+        assert_eq!(exif.mime, "image/png");
+        assert!(exif.entries.is_empty());
+        assert_eq!(exif.icc.unwrap().class, "mntr");
+    }
+}
+
+const GDEPTH_NS: &str = "http://ns.google.com/photos/1.0/depthmap/";
+
+/// Extended XMP packet, long enough to need several chunks
+fn extended_xmp() -> String {
+    format!(
+        r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:GDepth="http://ns.google.com/photos/1.0/depthmap/" GDepth:Mime="image/jpeg" GDepth:Data="{}"/></rdf:RDF></x:xmpmeta>"#,
+        "A".repeat(1000)
+    )
+}
+
+/// JPEG image whose main XMP packet refers to `guid`, followed by the chunks
+/// of the extended packet in the given order
+fn jpeg_with_extended_xmp(guid: &str, packet: &str, order: &[usize]) -> Vec<u8> {
+    let mut segments = vec![
+        exif_segment(&sample_tiff(true)),
+        xmp_segment(&xmp_with_extension(guid)),
+    ];
+    segments.extend(extended_xmp_segments(guid, packet.as_bytes(), 300, order));
+    jpeg(&segments)
+}
+
+fn depth_map(xmp: &XmpData) -> Option<usize> {
+    xmp.property(GDEPTH_NS, "Data")
+        .and_then(|p| p.value.as_str())
+        .map(|data| data.len())
+}
+
+#[test]
+fn extended_xmp_out_of_order() {
+    let packet = extended_xmp();
+    let guid = xmp_guid(packet.as_bytes());
+    // the second chunk is repeated
+    let image = jpeg_with_extended_xmp(&guid, &packet, &[4, 3, 1, 0, 2, 1]);
+    for res in parse_both(&image) {
+        let xmp = res.unwrap().xmp.unwrap();
+        assert!(xmp
+            .property("http://ns.adobe.com/xap/1.0/", "Rating")
+            .is_some());
+        assert_eq!(depth_map(&xmp), Some(1000));
+    }
+}
+
+#[test]
+fn extended_xmp_guid_mismatch() {
+    let packet = extended_xmp();
+    let guid = "0123456789ABCDEF0123456789ABCDEF";
+    let image = jpeg_with_extended_xmp(guid, &packet, &[0, 1, 2, 3, 4]);
+    let (res, warnings) = parse_buffer_quiet(&image);
+    let xmp = res.unwrap().xmp.unwrap();
+    assert!(xmp
+        .property("http://ns.adobe.com/xap/1.0/", "Rating")
+        .is_some());
+    assert_eq!(depth_map(&xmp), None);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("does not match GUID"),
+        "{}",
+        warnings[0]
+    );
+
+    // chunks of another GUID are ignored
+    let other = xmp_guid(b"other packet");
+    let mut segments = vec![
+        exif_segment(&sample_tiff(true)),
+        xmp_segment(&xmp_with_extension(&other)),
+    ];
+    segments.extend(extended_xmp_segments(
+        guid,
+        packet.as_bytes(),
+        300,
+        &[0, 1, 2, 3, 4],
+    ));
+    let (res, warnings) = parse_buffer_quiet(&jpeg(&segments));
+    assert_eq!(depth_map(&res.unwrap().xmp.unwrap()), None);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("not found"), "{}", warnings[0]);
+}
+
+#[test]
+fn extended_xmp_truncated() {
+    let packet = extended_xmp();
+    let guid = xmp_guid(packet.as_bytes());
+    // a chunk in the middle, then the last one, are missing
+    for (order, message) in &[
+        (&[0, 2, 3, 4][..], "missing bytes at offset 300"),
+        (&[0, 1, 2, 3][..], "expected"),
+    ] {
+        let image = jpeg_with_extended_xmp(&guid, &packet, order);
+        let (res, warnings) = parse_buffer_quiet(&image);
+        let xmp = res.unwrap().xmp.unwrap();
+        assert!(xmp
+            .property("http://ns.adobe.com/xap/1.0/", "Rating")
+            .is_some());
+        assert_eq!(depth_map(&xmp), None);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains(message), "{}", warnings[0]);
+    }
+}
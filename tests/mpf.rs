@@ -0,0 +1,152 @@
+mod common;
+
+use common::*;
+use rexif::*;
+use std::io::Cursor;
+
+/// APP2 segment of the Multi-Picture Format: a TIFF structure whose first IFD
+/// is followed by a second one, if any
+fn mpf_segment(le: bool, first: &[Entry], second: &[Entry]) -> Vec<u8> {
+    let mut tiff = if le {
+        b"II*\0".to_vec()
+    } else {
+        b"MM\0*".to_vec()
+    };
+    tiff.extend(u32s(le, &[8]));
+    let next = if second.is_empty() {
+        0
+    } else {
+        8 + ifd_length(first)
+    };
+    write_ifd(le, &mut tiff, first, next);
+    if !second.is_empty() {
+        write_ifd(le, &mut tiff, second, 0);
+    }
+    let mut data = b"MPF\0".to_vec();
+    data.extend(tiff);
+    segment(0xe2, &data)
+}
+
+/// MP Index IFD listing the images, given their attribute, size and offset
+fn mp_index(le: bool, images: &[(u32, u32, u32)]) -> Vec<Entry> {
+    let entries: Vec<u8> = images
+        .iter()
+        .flat_map(|&(attribute, size, offset)| u32s(le, &[attribute, size, offset, 0]))
+        .collect();
+    vec![
+        undefined(0xb000, b"0100"),
+        long(le, 0xb001, &[images.len() as u32]),
+        undefined(0xb002, &entries),
+    ]
+}
+
+/// Primary image with EXIF data, followed by a disparity image. Returns the
+/// file and the range of the secondary image.
+fn stereo_pair(le: bool) -> (Vec<u8>, std::ops::Range<usize>) {
+    let secondary = jpeg(&[mpf_segment(
+        le,
+        &[
+            long(le, 0xb101, &[2]),
+            (0xb205, 10, 1, u32s(le, &[-15i32 as u32, 10])),
+        ],
+        &[],
+    )]);
+    let individual_num = [long(le, 0xb101, &[1])];
+    let primary = |index: &[Entry]| {
+        jpeg(&[
+            exif_segment(&sample_tiff(le)),
+            mpf_segment(le, index, &individual_num),
+        ])
+    };
+
+    // offsets are relative to the TIFF header of the MPF segment of the primary
+    // image, that follows SOI, the EXIF segment and the MPF marker, length and name
+    let header = 2 + exif_segment(&sample_tiff(le)).len() + 8;
+    let primary_length = primary(&mp_index(le, &[(0, 0, 0), (0, 0, 0)])).len();
+    let index = mp_index(
+        le,
+        &[
+            (0x2003_0000, primary_length as u32, 0),
+            (
+                0x0002_0002,
+                secondary.len() as u32,
+                (primary_length - header) as u32,
+            ),
+        ],
+    );
+    let mut image = primary(&index);
+    assert_eq!(image.len(), primary_length);
+    image.extend(&secondary);
+    (image, primary_length..primary_length + secondary.len())
+}
+
+fn readable(entries: &[ExifEntry], tag: ExifTag) -> Option<&str> {
+    entries
+        .iter()
+        .find(|e| e.tag == tag)
+        .map(|e| &e.value_more_readable[..])
+}
+
+#[test]
+fn stereo_pair_index() {
+    for &le in &[true, false] {
+        let (image, secondary) = stereo_pair(le);
+        let from_buffer = parse_buffer_quiet(&image);
+        let from_reader = parse_reader_quiet(Cursor::new(&image));
+        for (res, warnings) in [from_buffer, from_reader] {
+            let exif = res.unwrap();
+            assert!(warnings.is_empty(), "{:?}", warnings);
+            assert!(readable(&exif.entries, ExifTag::Make).is_some());
+
+            let mpf = exif.mpf.unwrap();
+            assert_eq!(readable(&mpf.entries, ExifTag::MPFVersion), Some("0100"));
+            assert_eq!(readable(&mpf.entries, ExifTag::NumberOfImages), Some("2"));
+            assert!(mpf.entries.iter().all(|e| e.ifd_kind == IfdKind::MpIndex));
+            assert_eq!(mpf.images.len(), 2);
+
+            let primary = &mpf.images[0];
+            assert!(primary.is_representative());
+            assert_eq!(primary.image_type(), 0x03_0000);
+            assert_eq!(primary.range(), 0..secondary.start);
+            assert_eq!(
+                readable(&primary.attributes, ExifTag::MPIndividualNum),
+                Some("1")
+            );
+            assert!(primary
+                .attributes
+                .iter()
+                .all(|e| e.ifd_kind == IfdKind::MpAttributes));
+
+            let disparity = &mpf.images[1];
+            assert_eq!(disparity.image_type(), 0x02_0002);
+            assert_eq!(disparity.format(), 0);
+            assert_eq!(
+                readable(&disparity.attributes, ExifTag::MPIndividualNum),
+                Some("2")
+            );
+            assert_eq!(
+                readable(&disparity.attributes, ExifTag::ConvergenceAngle),
+                Some("-1.50°")
+            );
+            assert_eq!(
+                mpf.secondary_images().collect::<Vec<_>>(),
+                vec![secondary.clone()]
+            );
+            assert_eq!(&image[secondary.start..secondary.start + 2], &[0xff, 0xd8]);
+        }
+    }
+}
+
+#[test]
+fn truncated_secondary_image() {
+    let (image, secondary) = stereo_pair(true);
+    let image = &image[..secondary.start + 10];
+    let from_buffer = parse_buffer_quiet(image).0;
+    let from_reader = parse_reader_quiet(Cursor::new(image)).0;
+    for res in [from_buffer, from_reader] {
+        let mpf = res.unwrap().mpf.unwrap();
+        assert_eq!(mpf.images.len(), 2);
+        assert!(!mpf.images[0].attributes.is_empty());
+        assert!(mpf.images[1].attributes.is_empty());
+    }
+}
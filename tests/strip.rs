@@ -0,0 +1,222 @@
+mod common;
+
+use common::*;
+use rexif::*;
+
+const XMP_EXIF_NS: &str = "http://ns.adobe.com/exif/1.0/";
+const XMP_AUX_NS: &str = "http://ns.adobe.com/exif/1.0/aux/";
+const XMP_XMP_NS: &str = "http://ns.adobe.com/xap/1.0/";
+
+const XMP_WITH_GPS: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmlns:exif="http://ns.adobe.com/exif/1.0/" xmlns:aux="http://ns.adobe.com/exif/1.0/aux/" xmp:Rating="3" exif:GPSLatitude="45,30.2059N" aux:SerialNumber="SN654321"><exif:GPSLongitude>9,10.0000E</exif:GPSLongitude><exif:ExposureTime>1/125</exif:ExposureTime></rdf:Description></rdf:RDF></x:xmpmeta>"#;
+
+fn contains(image: &[u8], needle: &[u8]) -> bool {
+    image.windows(needle.len()).any(|w| w == needle)
+}
+
+fn has_tag(exif: &ExifData, tag: ExifTag) -> bool {
+    exif.entries.iter().any(|e| e.tag == tag)
+}
+
+/// Images of the sample EXIF data: TIFF and JPEG, in both byte orders
+fn images() -> Vec<Vec<u8>> {
+    let mut images = Vec::new();
+    for &le in &[true, false] {
+        images.push(sample_tiff(le));
+        images.push(jpeg(&[exif_segment(&sample_tiff(le))]));
+    }
+    images
+}
+
+fn strip_and_parse(image: &[u8], presets: &[StripPreset]) -> (Vec<u8>, ExifData) {
+    let stripped = strip(image, presets).unwrap();
+    let (res, warnings) = parse_buffer_quiet(&stripped);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    (stripped, res.unwrap())
+}
+
+#[test]
+fn gps() {
+    for image in images() {
+        let latitude = rational(true, 0, &[(45, 1), (30, 1), (1234, 100)]).3;
+        let latitude_be = rational(false, 0, &[(45, 1), (30, 1), (1234, 100)]).3;
+        assert!(contains(&image, &latitude) || contains(&image, &latitude_be));
+
+        let (stripped, exif) = strip_and_parse(&image, &[StripPreset::Gps]);
+        assert_eq!(stripped.len(), image.len());
+        assert!(exif.entries.iter().all(|e| e.ifd.ifd_kind != IfdKind::Gps));
+        assert!(!has_tag(&exif, ExifTag::GPSOffset));
+        assert!(!contains(&stripped, &latitude) && !contains(&stripped, &latitude_be));
+        for tag in &[
+            ExifTag::Make,
+            ExifTag::ExposureTime,
+            ExifTag::BodySerialNumber,
+        ] {
+            assert!(has_tag(&exif, *tag), "{:?}", tag);
+        }
+        assert!(has_tag(&exif, ExifTag::InteroperabilityIndex));
+        assert!(exif.thumbnail().is_some());
+    }
+}
+
+#[test]
+fn serial_numbers() {
+    for image in images() {
+        let (stripped, exif) = strip_and_parse(&image, &[StripPreset::SerialNumbers]);
+        assert_eq!(stripped.len(), image.len());
+        assert!(!has_tag(&exif, ExifTag::BodySerialNumber));
+        assert!(!has_tag(&exif, ExifTag::CameraOwnerName));
+        assert!(!contains(&stripped, b"SN123456"));
+        assert!(!contains(&stripped, b"Owner Name"));
+        for tag in &[ExifTag::Make, ExifTag::ExposureTime, ExifTag::GPSLatitude] {
+            assert!(has_tag(&exif, *tag), "{:?}", tag);
+        }
+    }
+}
+
+#[test]
+fn maker_note() {
+    for image in images() {
+        let (stripped, exif) = strip_and_parse(&image, &[StripPreset::MakerNote]);
+        assert_eq!(stripped.len(), image.len());
+        assert!(!has_tag(&exif, ExifTag::MakerNote));
+        assert!(!contains(&stripped, b"MAKERNOTEDATA"));
+        for tag in &[
+            ExifTag::ExposureTime,
+            ExifTag::BodySerialNumber,
+            ExifTag::GPSLatitude,
+        ] {
+            assert!(has_tag(&exif, *tag), "{:?}", tag);
+        }
+    }
+}
+
+#[test]
+fn all_exif() {
+    for &le in &[true, false] {
+        let image = jpeg(&[exif_segment(&sample_tiff(le)), xmp_segment(XMP)]);
+        let stripped = strip(&image, &[StripPreset::AllExif]).unwrap();
+        assert_eq!(stripped, jpeg(&[xmp_segment(XMP)]));
+
+        let (stripped, exif) = strip_and_parse(&sample_tiff(le), &[StripPreset::AllExif]);
+        assert!(has_tag(&exif, ExifTag::Orientation));
+        assert!(has_tag(&exif, ExifTag::XResolution));
+        for tag in &[
+            ExifTag::Make,
+            ExifTag::Artist,
+            ExifTag::ExposureTime,
+            ExifTag::GPSLatitude,
+        ] {
+            assert!(!has_tag(&exif, *tag), "{:?}", tag);
+        }
+        assert!(!contains(&stripped, b"Jane Doe"));
+        assert!(!contains(&stripped, b"SN123456"));
+        assert!(!contains(&stripped, b"MAKERNOTEDATA"));
+    }
+}
+
+fn check_xmp(xmp: &XmpData, gps: bool, serial: bool) {
+    assert_eq!(xmp.property(XMP_EXIF_NS, "GPSLatitude").is_some(), gps);
+    assert_eq!(xmp.property(XMP_EXIF_NS, "GPSLongitude").is_some(), gps);
+    assert_eq!(xmp.property(XMP_AUX_NS, "SerialNumber").is_some(), serial);
+    assert!(xmp.property(XMP_EXIF_NS, "ExposureTime").is_some());
+    assert!(xmp.property(XMP_XMP_NS, "Rating").is_some());
+}
+
+#[test]
+fn xmp() {
+    let image = jpeg(&[exif_segment(&sample_tiff(true)), xmp_segment(XMP_WITH_GPS)]);
+    check_xmp(
+        &parse_buffer_quiet(&image).0.unwrap().xmp.unwrap(),
+        true,
+        true,
+    );
+
+    let (stripped, exif) = strip_and_parse(&image, &[StripPreset::Gps]);
+    assert_eq!(stripped.len(), image.len());
+    check_xmp(&exif.xmp.unwrap(), false, true);
+    assert!(!contains(&stripped, b"45,30.2059N"));
+    assert!(!contains(&stripped, b"9,10.0000E"));
+
+    let (_, exif) = strip_and_parse(&image, &[StripPreset::SerialNumbers]);
+    check_xmp(&exif.xmp.unwrap(), true, false);
+    // and the EXIF segment is dropped
+    let stripped = strip(&image, &[StripPreset::AllExif]).unwrap();
+    let exif = parse_buffer_quiet(&stripped).0.unwrap();
+    assert!(exif.entries.is_empty());
+    check_xmp(&exif.xmp.unwrap(), false, false);
+
+    // not stripped when it can't be parsed
+    let image = jpeg(&[xmp_segment("<x:xmpmeta>")]);
+    assert!(strip(&image, &[StripPreset::Gps]).is_err());
+    assert!(strip(&image, &[StripPreset::MakerNote]).is_ok());
+}
+
+#[test]
+fn xmp_of_tiff() {
+    for &le in &[true, false] {
+        let mut ifds = sample_ifds(le);
+        let packet = XMP_WITH_GPS.as_bytes();
+        ifds.ifd0
+            .push((700, 1, packet.len() as u32, packet.to_vec()));
+        let image = tiff(le, &ifds);
+        let (_, exif) = strip_and_parse(&image, &[StripPreset::Gps]);
+        check_xmp(&exif.xmp.unwrap(), false, true);
+    }
+}
+
+#[test]
+fn photoshop_copy() {
+    // EXIF data only in the APP13 segment, as written by Photoshop
+    let tiff = sample_tiff(false);
+    let image = jpeg(&[photoshop_segment(&irb(&[(1058, &tiff)]))]);
+    assert!(has_tag(
+        &parse_buffer_quiet(&image).0.unwrap(),
+        ExifTag::GPSLatitude
+    ));
+
+    let (stripped, exif) = strip_and_parse(&image, &[StripPreset::Gps, StripPreset::SerialNumbers]);
+    assert_eq!(stripped.len(), image.len());
+    assert!(has_tag(&exif, ExifTag::ExposureTime));
+    assert!(!has_tag(&exif, ExifTag::GPSLatitude));
+    assert!(!contains(&stripped, b"SN123456"));
+}
+
+#[test]
+fn extended_xmp() {
+    let packet = format!(
+        r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description rdf:about="" xmlns:exif="http://ns.adobe.com/exif/1.0/" xmlns:aux="http://ns.adobe.com/exif/1.0/aux/" xmlns:GDepth="http://ns.google.com/photos/1.0/depthmap/" exif:GPSLatitude="45,30.2059N" aux:SerialNumber="SN654321" GDepth:Data="{}"/></rdf:RDF></x:xmpmeta>"#,
+        "A".repeat(1000)
+    );
+    let guid = xmp_guid(packet.as_bytes());
+    let mut segments = vec![
+        exif_segment(&sample_tiff(true)),
+        xmp_segment(&xmp_with_extension(&guid)),
+    ];
+    segments.extend(extended_xmp_segments(
+        &guid,
+        packet.as_bytes(),
+        300,
+        &[2, 0, 1, 3, 4],
+    ));
+    let image = jpeg(&segments);
+
+    let (stripped, exif) = strip_and_parse(&image, &[StripPreset::Gps]);
+    assert_eq!(stripped.len(), image.len());
+    assert!(!contains(&stripped, b"45,30.2059N"));
+    assert!(!contains(&stripped, guid.as_bytes()));
+    let xmp = exif.xmp.unwrap();
+    assert!(xmp.property(XMP_EXIF_NS, "GPSLatitude").is_none());
+    assert!(xmp.property(XMP_AUX_NS, "SerialNumber").is_some());
+    let depth_map = xmp.property("http://ns.google.com/photos/1.0/depthmap/", "Data");
+    assert_eq!(depth_map.unwrap().value.as_str().map(str::len), Some(1000));
+
+    let (stripped, exif) = strip_and_parse(&image, &[StripPreset::SerialNumbers]);
+    assert!(!contains(&stripped, b"SN654321"));
+    let xmp = exif.xmp.unwrap();
+    assert!(xmp.property(XMP_EXIF_NS, "GPSLatitude").is_some());
+    assert!(xmp.property(XMP_AUX_NS, "SerialNumber").is_none());
+
+    // left alone when there is nothing to remove
+    let stripped = strip(&image, &[StripPreset::MakerNote]).unwrap();
+    assert!(contains(&stripped, guid.as_bytes()));
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::*;
+use rexif::*;
+
+fn parse(le: bool, ifds: &Ifds) -> ExifData {
+    let (res, warnings) = parse_buffer_quiet(&tiff(le, ifds));
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    res.unwrap()
+}
+
+fn entry(exif: &ExifData, tag: ExifTag) -> &ExifEntry {
+    exif.entries
+        .iter()
+        .find(|e| e.tag == tag)
+        .unwrap_or_else(|| panic!("{:?} not found", tag))
+}
+
+fn readable(exif: &ExifData, tag: ExifTag) -> &str {
+    &entry(exif, tag).value_more_readable
+}
+
+#[test]
+fn image_structure() {
+    for &le in &[true, false] {
+        let exif = parse(
+            le,
+            &Ifds {
+                ifd0: vec![
+                    long(le, 0x00fe, &[1]),
+                    long(le, 0x0100, &[640]),
+                    short(le, 0x0101, &[480]),
+                    short(le, 0x0102, &[8, 8, 8]),
+                    short(le, 0x0103, &[5]),
+                    short(le, 0x0106, &[2]),
+                    long(le, 0x0111, &[8]),
+                    short(le, 0x0115, &[3]),
+                    long(le, 0x0116, &[480]),
+                    long(le, 0x0117, &[921_600]),
+                    short(le, 0x011c, &[1]),
+                    short(le, 0x013d, &[2]),
+                ],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::NewSubfileType),
+            "Reduced-resolution image"
+        );
+        assert!(
+            matches!(entry(&exif, ExifTag::ImageWidth).value, TagValue::U32(ref v) if v == &[640])
+        );
+        assert!(
+            matches!(entry(&exif, ExifTag::ImageLength).value, TagValue::U16(ref v) if v == &[480])
+        );
+        assert_eq!(readable(&exif, ExifTag::BitsPerSample), "8, 8, 8");
+        assert_eq!(readable(&exif, ExifTag::Compression), "LZW");
+        assert_eq!(readable(&exif, ExifTag::PhotometricInterpretation), "RGB");
+        assert_eq!(readable(&exif, ExifTag::SamplesPerPixel), "3");
+        assert_eq!(readable(&exif, ExifTag::RowsPerStrip), "480");
+        assert_eq!(readable(&exif, ExifTag::PlanarConfiguration), "Chunky");
+        assert_eq!(
+            readable(&exif, ExifTag::Predictor),
+            "Horizontal differencing"
+        );
+    }
+}
+
+#[test]
+fn offset_times_and_sensitivity() {
+    for &le in &[true, false] {
+        let exif = parse(
+            le,
+            &Ifds {
+                exif: vec![
+                    short(le, 0x8830, &[2]),
+                    long(le, 0x8832, &[400]),
+                    long(le, 0x8833, &[400]),
+                    ascii(0x9010, "+02:00"),
+                    ascii(0x9011, "-05:30"),
+                    ascii(0x9290, "123"),
+                    ascii(0x9291, "45"),
+                    (0x9400, 10, 1, u32s(le, &[-5i32 as u32, 10])),
+                    short(le, 0xa460, &[2]),
+                ],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::SensitivityType),
+            "Recommended exposure index (REI)"
+        );
+        assert_eq!(
+            readable(&exif, ExifTag::RecommendedExposureIndex),
+            "ISO 400"
+        );
+        assert_eq!(readable(&exif, ExifTag::ISOSpeed), "ISO 400");
+        assert_eq!(readable(&exif, ExifTag::OffsetTime), "+02:00");
+        assert_eq!(readable(&exif, ExifTag::OffsetTimeOriginal), "-05:30");
+        assert_eq!(readable(&exif, ExifTag::SubSecTime), "123");
+        assert_eq!(readable(&exif, ExifTag::SubSecTimeOriginal), "45");
+        assert_eq!(readable(&exif, ExifTag::Temperature), "-0.5 °C");
+        assert_eq!(
+            readable(&exif, ExifTag::CompositeImage),
+            "General composite image"
+        );
+    }
+}
+
+#[test]
+fn utf8_text() {
+    let le = true;
+    let mut artist = "Zoë Müller".as_bytes().to_vec();
+    artist.push(0);
+    let exif = parse(
+        le,
+        &Ifds {
+            ifd0: vec![(0x013b, 129, artist.len() as u32, artist)],
+            exif: vec![
+                ascii(0xa436, "Title"),
+                // No terminating NUL
+                (0xa437, 129, 6, "Jérô".as_bytes().to_vec()),
+            ],
+            ..Default::default()
+        },
+    );
+    assert!(
+        matches!(entry(&exif, ExifTag::Artist).value, TagValue::Utf8(ref s) if s == "Zoë Müller")
+    );
+    assert_eq!(readable(&exif, ExifTag::Artist), "Zoë Müller");
+    assert!(
+        matches!(entry(&exif, ExifTag::ImageTitle).value, TagValue::Ascii(ref s) if s == "Title")
+    );
+    assert_eq!(readable(&exif, ExifTag::Photographer), "Jérô");
+}
+
+fn utf16(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .flat_map(|c| c.to_le_bytes().to_vec())
+        .collect()
+}
+
+#[test]
+fn windows_xp_tags() {
+    for &le in &[true, false] {
+        let mut keywords = utf16("sea; sunset;;boat ");
+        keywords.extend_from_slice(&[0, 0]);
+        // Odd byte count: the trailing byte is ignored
+        let mut title = utf16("Title");
+        title.extend_from_slice(&[0, 0, 0x41]);
+        // No terminating NUL
+        let subject = utf16("Sub");
+        let exif = parse(
+            le,
+            &Ifds {
+                ifd0: vec![
+                    short(le, 0x4746, &[4]),
+                    short(le, 0x4749, &[75]),
+                    (0x9c9b, 1, title.len() as u32, title),
+                    (0x9c9e, 1, keywords.len() as u32, keywords),
+                    (0x9c9f, 1, subject.len() as u32, subject),
+                ],
+                ..Default::default()
+            },
+        );
+        assert_eq!(readable(&exif, ExifTag::Rating), "4 stars");
+        assert_eq!(readable(&exif, ExifTag::RatingPercent), "75%");
+        assert!(
+            matches!(entry(&exif, ExifTag::XPTitle).value, TagValue::Utf8(ref s) if s == "Title")
+        );
+        assert_eq!(readable(&exif, ExifTag::XPTitle), "Title");
+        assert_eq!(readable(&exif, ExifTag::XPSubject), "Sub");
+        assert_eq!(readable(&exif, ExifTag::XPKeywords), "sea; sunset;;boat ");
+        assert_eq!(exif.xp_keywords(), vec!["sea", "sunset", "boat"]);
+    }
+}
+
+#[test]
+fn odd_xp_tag() {
+    let exif = parse(
+        true,
+        &Ifds {
+            ifd0: vec![
+                (0x9c9c, 1, 3, vec![0x41, 0, 0x42]),
+                (0x9c9e, 1, 1, vec![0x41]),
+            ],
+            ..Default::default()
+        },
+    );
+    assert_eq!(readable(&exif, ExifTag::XPComment), "A");
+    assert!(exif.xp_keywords().is_empty());
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::*;
+use rexif::*;
+
+/// Entries that do not hold offsets, which change with the layout
+fn summary(exif: &ExifData) -> Vec<String> {
+    exif.entries
+        .iter()
+        .filter(|e| {
+            !matches!(
+                e.tag,
+                ExifTag::ExifOffset
+                    | ExifTag::GPSOffset
+                    | ExifTag::InteroperabilityOffset
+                    | ExifTag::JPEGInterchangeFormat
+            )
+        })
+        .map(|e| format!("{:?} {:?} {}", e.ifd.ifd_kind, e.tag, e.value_more_readable))
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    for &le in &[true, false] {
+        let exif = parse_buffer_quiet(&sample_tiff(le)).0.unwrap();
+        let kinds = [
+            IfdKind::Ifd0,
+            IfdKind::Exif,
+            IfdKind::Gps,
+            IfdKind::Interop,
+            IfdKind::Ifd1,
+        ];
+        for kind in &kinds {
+            assert!(exif.entries.iter().any(|e| e.ifd.ifd_kind == *kind));
+        }
+
+        for &out_le in &[true, false] {
+            let tiff = exif.serialize(out_le).unwrap();
+            assert_eq!(&tiff[0..2], if out_le { b"II" } else { b"MM" });
+            let (res, warnings) = parse_buffer_quiet(&tiff);
+            let parsed = res.unwrap();
+            assert!(warnings.is_empty(), "{:?}", warnings);
+            assert_eq!(summary(&parsed), summary(&exif));
+            assert_eq!(parsed.thumbnail(), Some(THUMBNAIL));
+            assert!(parsed.entries.iter().all(|e| e.ifd.le == out_le));
+
+            // serializing again gives the same block
+            assert_eq!(parsed.serialize(out_le).unwrap(), tiff);
+        }
+    }
+}
+
+#[test]
+fn serialize_selected_entries() {
+    let exif = parse_buffer_quiet(&sample_tiff(true)).0.unwrap();
+    let entries = exif
+        .entries
+        .iter()
+        .filter(|e| e.ifd.ifd_kind != IfdKind::Gps)
+        .map(|e| &e.ifd);
+    let tiff = serialize_tiff(entries, true).unwrap();
+    let parsed = parse_buffer_quiet(&tiff).0.unwrap();
+    assert!(parsed
+        .entries
+        .iter()
+        .all(|e| e.ifd.ifd_kind != IfdKind::Gps));
+    assert!(parsed.entries.iter().all(|e| e.tag != ExifTag::GPSOffset));
+    assert!(parsed
+        .entries
+        .iter()
+        .any(|e| e.tag == ExifTag::ExposureTime));
+}
+
+fn jfif() -> Vec<u8> {
+    segment(0xe0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0")
+}
+
+#[test]
+fn replace_exif() {
+    let old = sample_tiff(true);
+    let new = tiff(
+        false,
+        &Ifds {
+            ifd0: vec![ascii(0x010f, "Other")],
+            ..Default::default()
+        },
+    );
+    let image = jpeg(&[jfif(), exif_segment(&old), xmp_segment(XMP)]);
+    let replaced = replace_exif_in_jpeg(&image, Some(&new)).unwrap();
+    assert_eq!(
+        replaced,
+        jpeg(&[jfif(), exif_segment(&new), xmp_segment(XMP)])
+    );
+
+    let exif = parse_buffer_quiet(&replaced).0.unwrap();
+    assert_eq!(exif.entries.len(), 1);
+    assert_eq!(exif.entries[0].value_more_readable, "Other");
+    assert!(exif.xmp.is_some());
+}
+
+#[test]
+fn insert_exif() {
+    let new = sample_tiff(true);
+    // after the JFIF segment, or right after SOI
+    let image = jpeg(&[jfif(), xmp_segment(XMP)]);
+    let inserted = replace_exif_in_jpeg(&image, Some(&new)).unwrap();
+    assert_eq!(
+        inserted,
+        jpeg(&[jfif(), exif_segment(&new), xmp_segment(XMP)])
+    );
+    let image = jpeg(&[xmp_segment(XMP)]);
+    let inserted = replace_exif_in_jpeg(&image, Some(&new)).unwrap();
+    assert_eq!(inserted, jpeg(&[exif_segment(&new), xmp_segment(XMP)]));
+}
+
+#[test]
+fn remove_exif() {
+    let old = sample_tiff(true);
+    // duplicate EXIF segments are all removed
+    let image = jpeg(&[jfif(), exif_segment(&old), exif_segment(&old)]);
+    let removed = replace_exif_in_jpeg(&image, None).unwrap();
+    assert_eq!(removed, jpeg(&[jfif()]));
+    assert!(parse_buffer_quiet(&removed).0.is_err());
+}
+
+#[test]
+fn replace_exif_errors() {
+    let image = jpeg(&[jfif()]);
+    let huge = vec![0; 0x10000];
+    assert!(matches!(
+        replace_exif_in_jpeg(&image, Some(&huge)),
+        Err(ExifError::SerializeError(_))
+    ));
+    assert!(matches!(
+        replace_exif_in_jpeg(&sample_tiff(true), None),
+        Err(ExifError::FileTypeUnknown)
+    ));
+}